@@ -0,0 +1,1108 @@
+use color_eyre::Result;
+use crossterm::event::{
+    Event as CrosstermEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton,
+    MouseEvent, MouseEventKind,
+};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin, Position, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Scrollbar, ScrollbarOrientation, Table},
+};
+use std::io;
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessRefreshKind, Signal, UpdateKind};
+
+use crate::columns::{self, ColumnSetup};
+use crate::config::{Config, Panels};
+use crate::detail::{DetailView, ProcessDetail};
+use crate::dialog::{self, Dialog, SIGNALS, Target};
+use crate::disks::DisksPanel;
+use crate::event::{Event, EventHandler};
+use crate::format::ByteUnits;
+use crate::graphs::HistoryGraphs;
+use crate::header::Header;
+use crate::help::{Footer, Help};
+use crate::history::{self, History};
+use crate::keymap::{Action, KeyChord, Keymap, Scope};
+use crate::layout::{Arrangement, Heights};
+use crate::meters::CpuMeters;
+use crate::network::{NetworkPanel, NetworkStats};
+use crate::process::{self, Column, Context, Sort, TaskCounts};
+use crate::refresh::{self, Needs, RefreshPlanner, Sources};
+use crate::scroll::{self, ScrollingPanel, ScrollingText};
+use crate::search::SearchMode;
+use crate::sensors::{self, SensorsPanel};
+use crate::table::ProcessTable;
+use crate::theme::Theme;
+use crate::tui::{self, Tui};
+
+/// How often system data is sampled unless told otherwise.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The shortest sample interval we allow. sysinfo computes CPU usage as a delta between two
+/// refreshes and needs at least [`sysinfo::MINIMUM_CPU_UPDATE_INTERVAL`] between them (200 ms on
+/// Linux) for the numbers to mean anything.
+pub const MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+pub const MAX_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Rows moved per notch of the mouse wheel.
+const WHEEL_LINES: isize = 3;
+
+/// The intervals `+` and `-` step through.
+const INTERVAL_STEPS: [Duration; 10] = [
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(1500),
+    Duration::from_secs(2),
+    Duration::from_secs(3),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+];
+
+#[derive(Debug)]
+pub struct App {
+    running: bool,
+    /// The settings loaded at startup. Saving writes the current state over these, keeping
+    /// anything that can't be changed in the app.
+    config: Config,
+    keymap: Keymap,
+    theme: Theme,
+    sources: Sources,
+    planner: RefreshPlanner,
+    processes: ProcessTable,
+    /// The process table's columns, in display order.
+    columns: Vec<Column>,
+    tasks: TaskCounts,
+    interval: Duration,
+    units: ByteUnits,
+    show_frequency: bool,
+    history: History,
+    show_graphs: bool,
+    graph_window: Duration,
+    network: NetworkStats,
+    show_network: bool,
+    hide_virtual: bool,
+    show_disks: bool,
+    show_sensors: bool,
+    /// Whether the header shows the hottest CPU sensor.
+    show_cpu_temperature: bool,
+    /// When the last sample was taken, for turning per-sample counters into rates.
+    last_sample: Option<Instant>,
+    /// The process detail screen, shown in place of the table while open.
+    detail: Option<ProcessDetail>,
+    dialog: Option<Dialog>,
+    status: Option<Status>,
+    /// The panel the movement keys act on. Falls back to the table while it's hidden.
+    focus: Panel,
+    /// Rows scrolled past at the top of each panel that scrolls.
+    network_scroll: usize,
+    disks_scroll: usize,
+    sensors_scroll: usize,
+    /// Where things were drawn at the last render, for working out what a click hit.
+    areas: Areas,
+}
+
+/// The parts of the screen that can have focus.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Panel {
+    #[default]
+    Table,
+    Network,
+    Disks,
+    Sensors,
+}
+
+impl Panel {
+    const ALL: [Panel; 4] = [Panel::Table, Panel::Network, Panel::Disks, Panel::Sensors];
+}
+
+/// Where things were drawn at the last render. Hidden panels are left empty.
+#[derive(Debug, Default)]
+struct Areas {
+    table: Rect,
+    network: Rect,
+    disks: Rect,
+    sensors: Rect,
+    /// Each column title of the process table.
+    columns: Vec<(Column, Rect)>,
+}
+
+impl Areas {
+    fn panel(&self, panel: Panel) -> Rect {
+        match panel {
+            Panel::Table => self.table,
+            Panel::Network => self.network,
+            Panel::Disks => self.disks,
+            Panel::Sensors => self.sensors,
+        }
+    }
+
+    fn panel_at(&self, position: Position) -> Option<Panel> {
+        Panel::ALL
+            .into_iter()
+            .find(|&panel| self.panel(panel).contains(position))
+    }
+}
+
+/// A one-line message shown under the process table, such as the result of sending a signal.
+#[derive(Debug)]
+struct Status {
+    text: String,
+    is_error: bool,
+}
+
+impl Status {
+    fn info(text: String) -> Self {
+        Self {
+            text,
+            is_error: false,
+        }
+    }
+
+    fn error(text: String) -> Self {
+        Self {
+            text,
+            is_error: true,
+        }
+    }
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new(
+            Config::default(),
+            Keymap::default(),
+            Theme::default(),
+            ByteUnits::default(),
+        )
+    }
+}
+
+impl App {
+    pub fn new(config: Config, keymap: Keymap, theme: Theme, units: ByteUnits) -> Self {
+        let mut processes = ProcessTable::default();
+        processes.set_sort(config.sort);
+        if config.tree {
+            processes.toggle_tree();
+        }
+        let panels = &config.panels;
+        let mut app = Self {
+            running: true,
+            keymap,
+            theme,
+            sources: Sources::default(),
+            planner: RefreshPlanner::default(),
+            processes,
+            columns: config.columns.clone(),
+            tasks: TaskCounts::default(),
+            interval: config.interval().clamp(MIN_INTERVAL, MAX_INTERVAL),
+            units,
+            show_frequency: panels.frequency,
+            history: History::default(),
+            show_graphs: panels.graphs,
+            graph_window: config.graph_window(),
+            network: NetworkStats::default(),
+            show_network: panels.network,
+            hide_virtual: panels.hide_virtual,
+            show_disks: panels.disks,
+            show_sensors: panels.sensors,
+            show_cpu_temperature: panels.cpu_temperature,
+            last_sample: None,
+            detail: None,
+            dialog: None,
+            status: None,
+            focus: Panel::default(),
+            network_scroll: 0,
+            disks_scroll: 0,
+            sensors_scroll: 0,
+            areas: Areas::default(),
+            config,
+        };
+        app.sample();
+        app
+    }
+
+    pub fn run(&mut self, terminal: &mut Tui) -> Result<()> {
+        self.running = true;
+        let mut events = EventHandler::new(self.interval);
+        let mut redraw = true;
+        while self.running {
+            if redraw {
+                tui::draw(terminal, |frame| self.render(frame))?;
+            }
+            redraw = match events.next()? {
+                Event::Tick => {
+                    self.sample();
+                    true
+                }
+                Event::Crossterm(event) => self.handle_crossterm_event(event),
+            };
+            if events.tick_rate() != self.interval {
+                events.set_tick_rate(self.interval);
+            }
+        }
+        Ok(())
+    }
+
+    fn sample(&mut self) {
+        let needs = self.needs();
+        self.planner.refresh(&mut self.sources, needs);
+        let now = Instant::now();
+        self.history.record(&self.sources.system, now);
+        if needs.networks {
+            self.network.record(&self.sources.networks, now);
+        }
+        if let Some(detail) = &mut self.detail {
+            detail.update(
+                &self.sources.system,
+                &self.sources.users,
+                &self.sources.groups,
+                now,
+            );
+        }
+        let elapsed = self.last_sample.map(|last| now.duration_since(last));
+        self.last_sample = Some(now);
+        let context = Context {
+            users: &self.sources.users,
+            elapsed,
+            total_memory: self.sources.system.total_memory(),
+            scheduling: self
+                .table_columns()
+                .any(|column| matches!(column, Column::Priority | Column::Nice)),
+        };
+        let rows = process::collect(&self.sources.system, &context);
+        self.tasks = TaskCounts::count(&rows);
+        self.processes.update(rows);
+    }
+
+    /// What the header and process table currently display.
+    fn needs(&self) -> Needs {
+        // CPU usage is a delta between refreshes, so it's always kept up to date; a column that
+        // only starts reading it when shown would show nothing until the sample after.
+        let mut processes = ProcessRefreshKind::nothing().with_cpu();
+        let mut users = false;
+        for column in self.table_columns() {
+            users |= column.refresh_kind(&mut processes);
+        }
+        // Searches also look at command lines and users. Neither changes over a process's
+        // lifetime, so they only need reading once.
+        let searching = self.processes.search().is_editing() || self.processes.search().is_active();
+        if searching {
+            processes = processes
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_user(UpdateKind::OnlyIfNotSet);
+        }
+        Needs {
+            cpu: true,
+            memory: true,
+            swap: true,
+            processes: Some(processes),
+            users: users || searching,
+            networks: self.show_network,
+            disks: self.show_disks,
+            components: self.show_sensors || self.show_cpu_temperature,
+            detail: self.detail.as_ref().map(ProcessDetail::pid),
+        }
+    }
+
+    /// The shown columns, plus the sort column if it isn't one of them.
+    fn table_columns(&self) -> impl Iterator<Item = Column> + '_ {
+        let sort = self.processes.sort().column;
+        let hidden_sort = (!self.columns.contains(&sort)).then_some(sort);
+        self.columns.iter().copied().chain(hidden_sort)
+    }
+
+    fn render(&mut self, frame: &mut ratatui::Frame) {
+        const GRAPH_LINES: u16 = 12;
+
+        // The header grows to fit the CPU meters, up to half the screen; past that the meters
+        // switch to their compact form.
+        let arrangement = Arrangement::new(frame.area());
+        let area = arrangement.inner(frame.area());
+        let header_width = arrangement.header_width(area, self.show_graphs);
+        let max_header_height = area.height / 2;
+        let focus = self.focused_panel();
+        let theme = &self.theme;
+        let meters = CpuMeters::new(self.sources.system.cpus(), theme)
+            .show_frequency(self.show_frequency)
+            .fit(
+                header_width.saturating_sub(2),
+                Header::meter_budget(max_header_height),
+            );
+        let mut header = Header::new(
+            &self.sources.system,
+            self.tasks,
+            self.units,
+            self.interval,
+            meters,
+            theme,
+        )
+        .collapsed(arrangement.collapsed_header());
+        if self.show_cpu_temperature {
+            let reading = sensors::hottest_cpu(&self.sources.components)
+                .and_then(|sensor| Some((sensor.temperature()?, sensor.critical())));
+            header = header.cpu_temperature(reading);
+        }
+        let network = NetworkPanel::new(
+            &self.network,
+            self.units,
+            self.graph_window,
+            Instant::now(),
+            theme,
+        )
+        .hide_virtual(self.hide_virtual)
+        .scroll(self.network_scroll)
+        .focused(focus == Panel::Network);
+        let disks = DisksPanel::new(&self.sources.disks, self.units, theme)
+            .scroll(self.disks_scroll)
+            .focused(focus == Panel::Disks);
+        let sensors = SensorsPanel::new(&self.sources.components, theme)
+            .scroll(self.sensors_scroll)
+            .focused(focus == Panel::Sensors);
+
+        let heights = Heights {
+            header: header.height(header_width).min(max_header_height),
+            graphs: if self.show_graphs { GRAPH_LINES } else { 0 },
+            network: if self.show_network {
+                network.height()
+            } else {
+                0
+            },
+            disks: if self.show_disks { disks.height() } else { 0 },
+            sensors: if self.show_sensors {
+                sensors.height()
+            } else {
+                0
+            },
+        };
+        let screen = arrangement.split(area, heights);
+        let table_area = screen.table;
+
+        let sort = self.processes.sort();
+        let search = self.processes.search();
+        let highlight_matches = search.is_active() && search.mode() == SearchMode::Search;
+        let cells: Vec<Vec<String>> = self
+            .processes
+            .rows()
+            .map(|(process, prefix)| {
+                self.columns
+                    .iter()
+                    .map(|&column| {
+                        let cell = column.cell(process, self.units);
+                        if column == Column::Name {
+                            format!("{prefix}{cell}")
+                        } else {
+                            cell
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut content = vec![0; self.columns.len()];
+        for row in &cells {
+            for (width, cell) in content.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count() as u16);
+            }
+        }
+        // Less the borders. Columns that don't fit are left out rather than squeezed.
+        let available = table_area.width.saturating_sub(2);
+        let shown = columns::drop_to_fit(&self.columns, &content, available, sort.column);
+        let pick = |values: &[u16]| shown.iter().map(|&index| values[index]).collect::<Vec<_>>();
+        let shown_columns: Vec<Column> = shown.iter().map(|&index| self.columns[index]).collect();
+        let widths = columns::fit_widths(&shown_columns, &pick(&content), available);
+        let cells = cells.into_iter().map(|row| {
+            row.into_iter()
+                .enumerate()
+                .filter(|(index, _)| shown.contains(index))
+                .map(|(_, cell)| cell)
+                .collect::<Vec<_>>()
+        });
+        let rows = cells
+            .zip(self.processes.rows())
+            .map(|(cells, (process, _))| {
+                let row = ratatui::widgets::Row::new(cells);
+                if highlight_matches && search.matches(process) {
+                    row.style(theme.search_match)
+                } else {
+                    row
+                }
+            });
+
+        let title = if self.processes.is_tree() {
+            "Processes (tree)"
+        } else {
+            "Processes"
+        };
+        let titles = shown_columns.iter().map(|&column| {
+            if column == sort.column {
+                format!("{} {}", column.title(), sort.indicator())
+            } else {
+                column.title().to_string()
+            }
+        });
+
+        let widths: Vec<_> = widths.into_iter().map(Constraint::Length).collect();
+        // Laid out the way the table lays out its columns, to find which title a click is on.
+        let titles_area = Rect {
+            height: 1,
+            ..table_area.inner(Margin::new(1, 1))
+        };
+        let title_areas = Layout::horizontal(widths.clone())
+            .flex(Flex::Start)
+            .spacing(1)
+            .split(titles_area);
+        self.areas = Areas {
+            table: table_area,
+            network: screen.network,
+            disks: screen.disks,
+            sensors: screen.sensors,
+            columns: shown_columns
+                .iter()
+                .copied()
+                .zip(title_areas.iter().copied())
+                .collect(),
+        };
+
+        let table = Table::new(rows, widths)
+            .header(ratatui::widgets::Row::new(titles).style(theme.table_header))
+            .block(theme.panel_block(title, focus == Panel::Table))
+            .row_highlight_style(theme.selected);
+
+        self.processes
+            .set_page_height(scroll::visible_rows(table_area.height));
+
+        frame.render_widget(header, screen.header);
+        if self.show_graphs {
+            frame.render_widget(
+                HistoryGraphs::new(&self.history, self.graph_window, Instant::now(), theme),
+                screen.graphs,
+            );
+        }
+        if self.show_network {
+            frame.render_widget(network, screen.network);
+        }
+        if self.show_disks {
+            frame.render_widget(disks, screen.disks);
+        }
+        if self.show_sensors {
+            frame.render_widget(sensors, screen.sensors);
+        }
+        if let Some(detail) = &mut self.detail {
+            detail.fit(table_area, self.units, theme);
+            frame.render_widget(
+                DetailView::new(detail, self.units, self.graph_window, Instant::now(), theme),
+                table_area,
+            );
+        } else {
+            frame.render_stateful_widget(table, table_area, self.processes.state_mut());
+            frame.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight),
+                table_area.inner(Margin::new(0, 1)),
+                &mut self.processes.scrollbar_state(),
+            );
+        }
+        let search = self.processes.search();
+        if search.is_editing() {
+            self.render_search_bar(frame, screen.status);
+        } else if let Some(status) = &self.status {
+            let style = if status.is_error {
+                theme.error
+            } else {
+                theme.info
+            };
+            frame.render_widget(
+                Paragraph::new(status.text.as_str()).style(style),
+                screen.status,
+            );
+        } else if search.is_active() {
+            self.render_search_bar(frame, screen.status);
+        }
+        frame.render_widget(self.footer(), screen.footer);
+        if let Some(dialog) = &self.dialog {
+            dialog.render(frame, theme);
+        }
+    }
+
+    /// The shortcut bar for whatever currently has the keyboard.
+    fn footer(&self) -> Footer<'_> {
+        let fixed = |entries: &[(&str, &'static str)]| {
+            Footer::new(
+                entries
+                    .iter()
+                    .map(|&(key, label)| (key.to_string(), label))
+                    .collect(),
+                &self.theme,
+            )
+        };
+        match &self.dialog {
+            Some(Dialog::SignalPicker { .. }) => {
+                return fixed(&[("Enter", "Choose"), ("Esc", "Cancel")]);
+            }
+            Some(Dialog::ConfirmSignal { .. }) => return fixed(&[("y", "Send"), ("n", "Cancel")]),
+            Some(Dialog::ColumnSetup(_)) => {
+                return fixed(&[("Space", "Show/hide"), ("J/K", "Move"), ("Esc", "Done")]);
+            }
+            Some(Dialog::Help(_)) => return fixed(&[("j/k", "Scroll"), ("Esc", "Close")]),
+            None => {}
+        }
+        if self.processes.search().is_editing() {
+            return fixed(&[
+                ("Enter", "Done"),
+                ("Esc", "Cancel"),
+                ("Tab", "Filter/search"),
+                ("Ctrl-r", "Matching"),
+            ]);
+        }
+        if self.detail.is_some() {
+            return fixed(&[("Esc", "Back"), ("j/k", "Scroll"), ("PgDn/PgUp", "Page")]);
+        }
+        Footer::from_keymap(
+            &self.keymap,
+            &[
+                (Action::Help, "Help"),
+                (Action::Search, "Search"),
+                (Action::ColumnSetup, "Columns"),
+                (Action::ToggleTree, "Tree"),
+                (Action::SortNext, "Sort"),
+                (Action::Kill, "Kill"),
+                (Action::Details, "Details"),
+                (Action::Quit, "Quit"),
+            ],
+            &self.theme,
+        )
+    }
+
+    /// The query, how it's being matched, and how many processes it matches. Shows the cursor
+    /// while the bar has focus.
+    fn render_search_bar(&self, frame: &mut ratatui::Frame, area: Rect) {
+        let search = self.processes.search();
+        let prompt = format!("/{}", search.text());
+        let summary = if search.is_invalid() {
+            Span::styled("  invalid regex", self.theme.error)
+        } else {
+            let count = self.processes.match_count();
+            let noun = if count == 1 { "match" } else { "matches" };
+            Span::raw(format!("  {count} {noun}"))
+        };
+        let line = Line::from(vec![
+            Span::raw(prompt.clone()),
+            Span::styled(
+                format!(
+                    "  [{}, {}]",
+                    search.mode().label(),
+                    search.matching().label()
+                ),
+                self.theme.dim,
+            ),
+            summary,
+        ]);
+        frame.render_widget(Paragraph::new(line), area);
+        if search.is_editing() {
+            let width = prompt.chars().count() as u16;
+            frame.set_cursor_position((area.x + width.min(area.width), area.y));
+        }
+    }
+
+    /// Handles input, returning whether the screen needs redrawing. Drawing formats every row
+    /// to size the columns, so it's skipped for the pointer moving and keys being released.
+    fn handle_crossterm_event(&mut self, event: CrosstermEvent) -> bool {
+        match event {
+            CrosstermEvent::Key(key) if key.kind == KeyEventKind::Press => {
+                self.on_key_event(key);
+                true
+            }
+            CrosstermEvent::Mouse(mouse) => self.on_mouse_event(mouse),
+            // Nothing to do beyond redrawing; the layout is worked out afresh from the new size.
+            CrosstermEvent::Resize(_, _) => true,
+            _ => false,
+        }
+    }
+
+    fn on_key_event(&mut self, key: KeyEvent) {
+        // Ctrl-C always quits, even with a dialog open.
+        if key.modifiers == KeyModifiers::CONTROL && matches!(key.code, KeyCode::Char('c' | 'C')) {
+            self.quit();
+            return;
+        }
+        if let Some(dialog) = self.dialog.take() {
+            self.dialog = self.on_dialog_key(dialog, key);
+            return;
+        }
+        if self.processes.search().is_editing() {
+            self.on_search_key(key);
+            return;
+        }
+        if let Some(detail) = &mut self.detail {
+            let page = self.processes.page_height().max(1) as u16;
+            match key.code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Backspace | KeyCode::Char('q') => {
+                    self.detail = None
+                }
+                KeyCode::Down | KeyCode::Char('j') => detail.scroll_down(1),
+                KeyCode::Up | KeyCode::Char('k') => detail.scroll_up(1),
+                KeyCode::PageDown => detail.scroll_down(page),
+                KeyCode::PageUp => detail.scroll_up(page),
+                KeyCode::Home | KeyCode::Char('g') => detail.scroll_to_top(),
+                _ => {}
+            }
+            return;
+        }
+        self.status = None;
+        if let Some(action) = self.keymap.action(key, |scope| self.in_scope(scope)) {
+            self.perform(action);
+        }
+    }
+
+    /// Returns whether the event was one that's acted on; moves, drags, releases and the other
+    /// buttons are ignored.
+    fn on_mouse_event(&mut self, mouse: MouseEvent) -> bool {
+        let lines = match mouse.kind {
+            MouseEventKind::ScrollDown => WHEEL_LINES,
+            MouseEventKind::ScrollUp => -WHEEL_LINES,
+            MouseEventKind::Down(MouseButton::Left) => 0,
+            _ => return false,
+        };
+        self.on_click_or_wheel(mouse, lines);
+        true
+    }
+
+    /// A left click, or the wheel scrolling by `lines`.
+    fn on_click_or_wheel(&mut self, mouse: MouseEvent, lines: isize) {
+        // The help is the one dialog worth scrolling; the rest, and the search bar, keep the
+        // mouse from reaching what's behind them.
+        if let Some(Dialog::Help(help)) = &mut self.dialog {
+            help.scroll_by(lines);
+            return;
+        }
+        if self.dialog.is_some() || self.processes.search().is_editing() {
+            return;
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        let Some(panel) = self.areas.panel_at(position) else {
+            return;
+        };
+        if lines != 0 {
+            self.scroll_panel(panel, lines);
+            return;
+        }
+        self.status = None;
+        self.focus = panel;
+        if panel == Panel::Table && self.detail.is_none() {
+            self.click_table(position);
+        }
+    }
+
+    /// Sorts by a column whose title was clicked, or reverses the sort if it's already the sort
+    /// column; or selects a row that was clicked.
+    fn click_table(&mut self, position: Position) {
+        let table = self.areas.table;
+        // The titles are on the line below the top border, and the rows start after them.
+        let first_row = table.y + 2;
+        if position.y == table.y + 1 {
+            let column = self
+                .areas
+                .columns
+                .iter()
+                .find(|(_, area)| area.contains(position))
+                .map(|&(column, _)| column);
+            if let Some(column) = column {
+                let sort = self.processes.sort();
+                self.set_sort(if sort.column == column {
+                    sort.reversed()
+                } else {
+                    Sort::by(column)
+                });
+            }
+        } else if (first_row..table.bottom().saturating_sub(1)).contains(&position.y)
+            && let Some(index) = self.processes.row_at((position.y - first_row) as usize)
+        {
+            self.processes.select(index);
+        }
+    }
+
+    fn is_shown(&self, panel: Panel) -> bool {
+        match panel {
+            Panel::Table => true,
+            Panel::Network => self.show_network,
+            Panel::Disks => self.show_disks,
+            Panel::Sensors => self.show_sensors,
+        }
+    }
+
+    fn focused_panel(&self) -> Panel {
+        if self.is_shown(self.focus) {
+            self.focus
+        } else {
+            Panel::Table
+        }
+    }
+
+    fn focus_next(&mut self) {
+        let current = self.focused_panel();
+        let index = Panel::ALL
+            .iter()
+            .position(|&panel| panel == current)
+            .unwrap_or(0);
+        self.focus = Panel::ALL
+            .into_iter()
+            .cycle()
+            .skip(index + 1)
+            .find(|&panel| self.is_shown(panel))
+            .unwrap_or_default();
+    }
+
+    /// Scrolls `panel` by `lines`, down if positive. The table moves its selection, or scrolls
+    /// the detail screen while that's open.
+    fn scroll_panel(&mut self, panel: Panel, lines: isize) {
+        let (scroll, rows) = match panel {
+            Panel::Table => {
+                match &mut self.detail {
+                    Some(detail) => detail.scroll_by(lines),
+                    None => self.processes.select_relative(lines),
+                }
+                return;
+            }
+            Panel::Network => (
+                &mut self.network_scroll,
+                self.network.interfaces(self.hide_virtual).count(),
+            ),
+            Panel::Disks => (&mut self.disks_scroll, self.sources.disks.list().len()),
+            Panel::Sensors => (
+                &mut self.sensors_scroll,
+                self.sources.components.list().len(),
+            ),
+        };
+        let visible = scroll::visible_rows(self.areas.panel(panel).height);
+        *scroll = scroll
+            .saturating_add_signed(lines)
+            .min(rows.saturating_sub(visible));
+    }
+
+    /// Whether actions in `scope` apply right now.
+    fn in_scope(&self, scope: Scope) -> bool {
+        match scope {
+            Scope::Always => true,
+            Scope::Tree => self.processes.is_tree(),
+            Scope::Search => self.processes.search().is_active(),
+            Scope::Matches => self.in_search_mode(),
+        }
+    }
+
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.quit(),
+            Action::Help => self.dialog = Some(Dialog::Help(Help::new(&self.keymap, &self.theme))),
+            Action::FocusNext => self.focus_next(),
+            // The movement keys scroll whichever other panel has focus.
+            Action::Down
+            | Action::Up
+            | Action::PageDown
+            | Action::PageUp
+            | Action::First
+            | Action::Last
+                if self.focused_panel() != Panel::Table =>
+            {
+                let panel = self.focused_panel();
+                let page = scroll::visible_rows(self.areas.panel(panel).height).max(1) as isize;
+                let lines = match action {
+                    Action::Down => 1,
+                    Action::Up => -1,
+                    Action::PageDown => page,
+                    Action::PageUp => -page,
+                    Action::First => isize::MIN,
+                    _ => isize::MAX,
+                };
+                self.scroll_panel(panel, lines);
+            }
+            Action::ClearSearch => {
+                self.processes.search_mut().clear();
+                self.processes.apply_search();
+            }
+            Action::Search => self.open_search(),
+            Action::NextMatch => self.jump_to_match(true),
+            Action::PreviousMatch => self.jump_to_match(false),
+            Action::ToggleTree => self.processes.toggle_tree(),
+            Action::Expand => self.processes.expand(),
+            Action::Collapse => self.processes.collapse(),
+            Action::SlowDown => self.slow_down(),
+            Action::SpeedUp => self.speed_up(),
+            Action::ToggleFrequency => self.show_frequency = !self.show_frequency,
+            Action::ToggleGraphs => self.show_graphs = !self.show_graphs,
+            Action::CycleGraphWindow => self.cycle_graph_window(),
+            Action::ToggleNetwork => self.show_network = !self.show_network,
+            Action::ToggleVirtual => self.hide_virtual = !self.hide_virtual,
+            Action::ToggleDisks => self.show_disks = !self.show_disks,
+            Action::ToggleSensors => self.show_sensors = !self.show_sensors,
+            Action::ToggleCpuTemperature => self.show_cpu_temperature = !self.show_cpu_temperature,
+            Action::SortPrevious => self.set_sort(self.processes.sort().previous(&self.columns)),
+            Action::SortNext => self.set_sort(self.processes.sort().next(&self.columns)),
+            Action::SortByCpu => self.set_sort(Sort::by(Column::Cpu)),
+            Action::SortByMemory => self.set_sort(Sort::by(Column::Memory)),
+            Action::SortByPid => self.set_sort(Sort::by(Column::Pid)),
+            Action::SortByTime => self.set_sort(Sort::by(Column::Time)),
+            Action::ReverseSort => self.set_sort(self.processes.sort().reversed()),
+            Action::Down => self.processes.select_next(),
+            Action::Up => self.processes.select_previous(),
+            Action::PageDown => self.processes.page_down(),
+            Action::PageUp => self.processes.page_up(),
+            Action::First => self.processes.select_first(),
+            Action::Last => self.processes.select_last(),
+            Action::Kill => self.open_signal_picker(),
+            Action::Details => self.open_detail(),
+            Action::ColumnSetup => {
+                self.dialog = Some(Dialog::ColumnSetup(ColumnSetup::new(&self.columns)))
+            }
+            Action::SaveConfig => self.save_config(),
+        }
+    }
+
+    fn quit(&mut self) {
+        self.running = false;
+    }
+
+    /// Handles a key while the search bar has focus. In search mode the selection follows the
+    /// first match as the query is typed.
+    fn on_search_key(&mut self, key: KeyEvent) {
+        let search = self.processes.search_mut();
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc) => search.clear(),
+            (_, KeyCode::Enter) => search.accept(),
+            (_, KeyCode::Tab) => search.toggle_mode(),
+            (KeyModifiers::CONTROL, KeyCode::Char('r')) => search.cycle_matching(),
+            (KeyModifiers::CONTROL, KeyCode::Char('u')) => search.clear_text(),
+            (_, KeyCode::Backspace) => search.pop(),
+            (modifiers, KeyCode::Char(c)) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                search.push(c)
+            }
+            _ => {}
+        }
+        self.processes.apply_search();
+
+        let on_match = self
+            .processes
+            .selected()
+            .is_some_and(|row| self.processes.search().matches(row));
+        if self.in_search_mode() && !on_match {
+            self.processes.select_match(true);
+        }
+    }
+
+    /// Whether a query is in effect with non-matching rows still visible, which is when `n` and
+    /// `N` have matches to jump between.
+    fn in_search_mode(&self) -> bool {
+        let search = self.processes.search();
+        search.is_active() && search.mode() == SearchMode::Search
+    }
+
+    fn jump_to_match(&mut self, forward: bool) {
+        if !self.processes.select_match(forward) {
+            let text = self.processes.search().text();
+            self.status = Some(Status::error(format!("No process matches \"{text}\"")));
+        }
+    }
+
+    /// Handles a key while `dialog` is open and returns the dialog to show next, if any.
+    fn on_dialog_key(&mut self, dialog: Dialog, key: KeyEvent) -> Option<Dialog> {
+        match dialog {
+            Dialog::SignalPicker { target, selected } => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => None,
+                KeyCode::Down | KeyCode::Char('j') => Some(Dialog::SignalPicker {
+                    target,
+                    selected: (selected + 1).min(SIGNALS.len() - 1),
+                }),
+                KeyCode::Up | KeyCode::Char('k') => Some(Dialog::SignalPicker {
+                    target,
+                    selected: selected.saturating_sub(1),
+                }),
+                KeyCode::Enter => Some(Dialog::ConfirmSignal {
+                    target,
+                    signal: SIGNALS[selected],
+                }),
+                _ => Some(Dialog::SignalPicker { target, selected }),
+            },
+            Dialog::ConfirmSignal { target, signal } => match key.code {
+                KeyCode::Char('y' | 'Y') | KeyCode::Enter => {
+                    self.send_signal(&target, signal);
+                    None
+                }
+                KeyCode::Char('n' | 'N' | 'q') | KeyCode::Esc => None,
+                _ => Some(Dialog::ConfirmSignal { target, signal }),
+            },
+            Dialog::ColumnSetup(mut setup) => {
+                match (key.modifiers, key.code) {
+                    (_, KeyCode::Esc | KeyCode::Enter | KeyCode::F(2) | KeyCode::Char('q')) => {
+                        return None;
+                    }
+                    (KeyModifiers::SHIFT, KeyCode::Down) | (_, KeyCode::Char('J')) => {
+                        setup.move_down()
+                    }
+                    (KeyModifiers::SHIFT, KeyCode::Up) | (_, KeyCode::Char('K')) => setup.move_up(),
+                    (_, KeyCode::Down | KeyCode::Char('j')) => setup.select_next(),
+                    (_, KeyCode::Up | KeyCode::Char('k')) => setup.select_previous(),
+                    (_, KeyCode::Char(' ')) => setup.toggle(),
+                    _ => {}
+                }
+                // Changes show in the table behind the dialog straight away.
+                self.columns = setup.columns();
+                Some(Dialog::ColumnSetup(setup))
+            }
+            Dialog::Help(mut help) => {
+                let page = self.processes.page_height().max(1) as u16;
+                // The key that opened the help closes it again.
+                if self
+                    .keymap
+                    .keys(Action::Help)
+                    .any(|chord| chord == KeyChord::from(key))
+                {
+                    return None;
+                }
+                match key.code {
+                    KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => return None,
+                    KeyCode::Down | KeyCode::Char('j') => help.scroll_down(1),
+                    KeyCode::Up | KeyCode::Char('k') => help.scroll_up(1),
+                    KeyCode::PageDown => help.scroll_down(page),
+                    KeyCode::PageUp => help.scroll_up(page),
+                    KeyCode::Home | KeyCode::Char('g') => help.scroll_to_top(),
+                    _ => {}
+                }
+                Some(Dialog::Help(help))
+            }
+        }
+    }
+
+    fn open_signal_picker(&mut self) {
+        if let Some(row) = self.processes.selected() {
+            self.dialog = Some(Dialog::SignalPicker {
+                target: Target {
+                    pid: row.pid,
+                    name: row.name.clone(),
+                    start_time: row.start_time,
+                },
+                selected: 0,
+            });
+        }
+    }
+
+    /// The current state as settings, on top of those loaded at startup.
+    fn settings(&self) -> Config {
+        Config {
+            interval: self.interval.as_secs_f64(),
+            sort: self.processes.sort(),
+            columns: self.columns.clone(),
+            tree: self.processes.is_tree(),
+            panels: Panels {
+                graphs: self.show_graphs,
+                graph_window: self.graph_window.as_secs(),
+                network: self.show_network,
+                hide_virtual: self.hide_virtual,
+                disks: self.show_disks,
+                sensors: self.show_sensors,
+                cpu_temperature: self.show_cpu_temperature,
+                frequency: self.show_frequency,
+            },
+            ..self.config.clone()
+        }
+    }
+
+    fn save_config(&mut self) {
+        let config = self.settings();
+        self.status = Some(match config.save() {
+            Ok(path) => {
+                self.config = config;
+                Status::info(format!("Saved settings to {}", path.display()))
+            }
+            Err(err) => Status::error(format!("Could not save settings: {err:#}")),
+        });
+    }
+
+    fn open_detail(&mut self) {
+        let Some(pid) = self.processes.selected().map(|row| row.pid) else {
+            return;
+        };
+        // Read the process in full now rather than leaving the screen half empty until the next
+        // sample.
+        refresh::refresh_detail(&mut self.sources, pid);
+        let mut detail = ProcessDetail::new(pid);
+        detail.update(
+            &self.sources.system,
+            &self.sources.users,
+            &self.sources.groups,
+            Instant::now(),
+        );
+        self.detail = Some(detail);
+    }
+
+    fn open_search(&mut self) {
+        let search = self.processes.search();
+        let reading = search.is_editing() || search.is_active();
+        self.processes.search_mut().open();
+        // Command lines and users are only read while there's a search, so sample now rather
+        // than leave them missing, and the query matching nothing, until the next sample.
+        if !reading {
+            self.sample();
+        }
+    }
+
+    fn send_signal(&mut self, target: &Target, signal: Signal) {
+        let name = dialog::signal_name(signal);
+        // The dialog may have been open across samples; if the PID now belongs to another
+        // process, it's the one that was chosen that has exited.
+        let process = self.sources.system.process(target.pid).filter(|process| {
+            process.start_time() == target.start_time
+                && process.name().to_string_lossy() == target.name
+        });
+        let Some(process) = process else {
+            self.status = Some(Status::error(format!(
+                "{} has already exited",
+                target.describe()
+            )));
+            return;
+        };
+        self.status = Some(match process.kill_with(signal) {
+            Some(true) => Status::info(format!("Sent {name} to {}", target.describe())),
+            // sysinfo only reports success or failure; the reason (usually EPERM) is still in
+            // errno from the kill(2) call it just made.
+            Some(false) => Status::error(format!(
+                "Could not send {name} to {}: {}",
+                target.describe(),
+                io::Error::last_os_error()
+            )),
+            None => Status::error(format!("{name} is not supported on this platform")),
+        });
+    }
+
+    fn set_sort(&mut self, sort: Sort) {
+        self.processes.set_sort(sort);
+    }
+
+    fn cycle_graph_window(&mut self) {
+        let windows = history::WINDOWS;
+        let next = windows
+            .iter()
+            .position(|&window| window == self.graph_window)
+            .map_or(0, |index| (index + 1) % windows.len());
+        self.graph_window = windows[next];
+    }
+
+    /// Moves to the next longer interval in [`INTERVAL_STEPS`].
+    fn slow_down(&mut self) {
+        if let Some(&step) = INTERVAL_STEPS.iter().find(|&&step| step > self.interval) {
+            self.interval = step;
+        }
+    }
+
+    /// Moves to the next shorter interval in [`INTERVAL_STEPS`].
+    fn speed_up(&mut self) {
+        if let Some(&step) = INTERVAL_STEPS
+            .iter()
+            .rev()
+            .find(|&&step| step < self.interval)
+        {
+            self.interval = step;
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Command line arguments.
+
+use std::time::Duration;
+
+use clap::Parser;
+
+use crate::app::{MAX_INTERVAL, MIN_INTERVAL};
+use crate::format::ByteUnits;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Delay between samples, in seconds (fractions allowed, e.g. 0.5). Overrides the config file
+    #[arg(short = 'd', long, value_name = "SECS", value_parser = parse_interval)]
+    pub interval: Option<f64>,
+
+    /// Show sizes in powers of 1000 (kB, MB, ...) instead of 1024 (KiB, MiB, ...)
+    #[arg(long)]
+    pub si: bool,
+}
+
+impl Cli {
+    pub fn units(&self) -> ByteUnits {
+        if self.si {
+            ByteUnits::Si
+        } else {
+            ByteUnits::Binary
+        }
+    }
+}
+
+/// Accepts a number of seconds like top's `-d`, and rejects anything outside
+/// [`MIN_INTERVAL`]..=[`MAX_INTERVAL`] rather than silently clamping it.
+fn parse_interval(arg: &str) -> Result<f64, String> {
+    let secs: f64 = arg
+        .parse()
+        .map_err(|_| format!("`{arg}` is not a number of seconds"))?;
+    let interval = Duration::try_from_secs_f64(secs)
+        .map_err(|_| format!("`{arg}` is not a valid interval"))?;
+    if !(MIN_INTERVAL..=MAX_INTERVAL).contains(&interval) {
+        return Err(format!(
+            "interval must be between {} and {} seconds",
+            MIN_INTERVAL.as_secs_f64(),
+            MAX_INTERVAL.as_secs_f64()
+        ));
+    }
+    Ok(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn parse_interval_accepts_seconds_in_range() {
+        assert_eq!(parse_interval("1"), Ok(1.0));
+        assert_eq!(parse_interval("0.25"), Ok(0.25));
+        assert_eq!(parse_interval("60"), Ok(60.0));
+    }
+
+    #[test]
+    fn parse_interval_rejects_out_of_range() {
+        for arg in ["0", "0.1", "61"] {
+            let err = parse_interval(arg).unwrap_err();
+            assert!(err.contains("between 0.25 and 60 seconds"), "{arg}: {err}");
+        }
+    }
+
+    #[test]
+    fn parse_interval_rejects_non_numbers() {
+        assert_eq!(
+            parse_interval("fast"),
+            Err("`fast` is not a number of seconds".to_string())
+        );
+        assert_eq!(
+            parse_interval("NaN"),
+            Err("`NaN` is not a valid interval".to_string())
+        );
+        assert_eq!(
+            parse_interval("-1"),
+            Err("`-1` is not a valid interval".to_string())
+        );
+        assert!(parse_interval("inf").is_err());
+    }
+
+    #[test]
+    fn arguments_parse() {
+        Cli::command().debug_assert();
+        let cli = Cli::try_parse_from(["vtop", "-d", "2.5", "--si"]).unwrap();
+        assert_eq!(cli.interval, Some(2.5));
+        assert_eq!(cli.units(), ByteUnits::Si);
+        assert!(Cli::try_parse_from(["vtop", "--interval", "0"]).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+//! Choosing the process table's columns and fitting them to the screen.
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem, ListState},
+};
+
+use crate::process::Column;
+use crate::theme::Theme;
+
+/// Room kept next to each title for the sort indicator.
+const INDICATOR_WIDTH: u16 = 2;
+
+/// Below this, a flexible column is cut short enough that it's worth dropping another column to
+/// widen it.
+const FLEXIBLE_MIN_WIDTH: u16 = 16;
+
+/// Width for each of `columns` given the widest cell each one holds, so that together they fit
+/// in `available` with a space between neighbours.
+///
+/// Fixed columns get what their content needs, up to [`Column::max_width`]. The flexible ones
+/// share what's left: each gets up to an even share, and whatever a narrow one doesn't use goes
+/// to the others.
+pub fn fit_widths(columns: &[Column], content: &[u16], available: u16) -> Vec<u16> {
+    let natural: Vec<u16> = columns
+        .iter()
+        .zip(content)
+        .map(|(&column, &width)| {
+            let width = width.max(title_width(column));
+            if column.is_flexible() {
+                width
+            } else {
+                width.min(column.max_width())
+            }
+        })
+        .collect();
+
+    let spacing = columns.len().saturating_sub(1) as u16;
+    let fixed: u16 = columns
+        .iter()
+        .zip(&natural)
+        .filter(|(column, _)| !column.is_flexible())
+        .map(|(_, &width)| width)
+        .sum();
+    let mut remaining = available.saturating_sub(spacing + fixed);
+
+    let mut widths = natural.clone();
+    let mut flexible: Vec<usize> = (0..columns.len())
+        .filter(|&index| columns[index].is_flexible())
+        .collect();
+    // Narrowest first, so what they leave over is there for the wider ones.
+    flexible.sort_by_key(|&index| natural[index]);
+    let mut left = flexible.len() as u16;
+    for index in flexible {
+        let share = remaining / left;
+        widths[index] = natural[index].min(share);
+        remaining -= widths[index];
+        left -= 1;
+    }
+    widths
+}
+
+/// Which of `columns` to show when they can't all fit in `available`, as indices in display
+/// order. The lowest [`Column::priority`] go first, rightmost first among equals, until the
+/// fixed columns fit at their content width and the flexible ones at [`FLEXIBLE_MIN_WIDTH`];
+/// `keep`, the sort column, is never dropped.
+pub fn drop_to_fit(
+    columns: &[Column],
+    content: &[u16],
+    available: u16,
+    keep: Column,
+) -> Vec<usize> {
+    let needed = |column: Column, content: u16| {
+        let width = content.max(title_width(column));
+        if column.is_flexible() {
+            width.min(FLEXIBLE_MIN_WIDTH)
+        } else {
+            width.min(column.max_width())
+        }
+    };
+    let mut shown: Vec<usize> = (0..columns.len()).collect();
+    loop {
+        let spacing = shown.len().saturating_sub(1) as u16;
+        let total: u16 = shown
+            .iter()
+            .map(|&index| needed(columns[index], content[index]))
+            .sum();
+        if total + spacing <= available || shown.len() <= 1 {
+            return shown;
+        }
+        let drop = shown
+            .iter()
+            .enumerate()
+            .filter(|&(_, &index)| columns[index] != keep)
+            .min_by_key(|&(position, &index)| (columns[index].priority(), usize::MAX - position))
+            .map(|(position, _)| position);
+        match drop {
+            Some(position) => {
+                shown.remove(position);
+            }
+            None => return shown,
+        }
+    }
+}
+
+/// The width a column needs for its title and the sort indicator.
+fn title_width(column: Column) -> u16 {
+    column.title().chars().count() as u16 + INDICATOR_WIDTH
+}
+
+/// The setup screen: every column with a checkbox, in the order they'd appear.
+#[derive(Debug, Clone)]
+pub struct ColumnSetup {
+    /// Shown columns in display order, followed by the hidden ones.
+    entries: Vec<(Column, bool)>,
+    selected: usize,
+}
+
+impl ColumnSetup {
+    pub fn new(shown: &[Column]) -> Self {
+        let hidden = Column::ALL
+            .into_iter()
+            .filter(|column| !shown.contains(column));
+        let entries = shown
+            .iter()
+            .map(|&column| (column, true))
+            .chain(hidden.map(|column| (column, false)))
+            .collect();
+        Self {
+            entries,
+            selected: 0,
+        }
+    }
+
+    /// The checked columns, in order.
+    pub fn columns(&self) -> Vec<Column> {
+        self.entries
+            .iter()
+            .filter(|(_, shown)| *shown)
+            .map(|&(column, _)| column)
+            .collect()
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(self.entries.len() - 1);
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Shows or hides the selected column. The last shown column can't be hidden.
+    pub fn toggle(&mut self) {
+        let shown = self.entries.iter().filter(|(_, shown)| *shown).count();
+        let entry = &mut self.entries[self.selected];
+        if !entry.1 || shown > 1 {
+            entry.1 = !entry.1;
+        }
+    }
+
+    /// Moves the selected column one place earlier.
+    pub fn move_up(&mut self) {
+        if self.selected > 0 {
+            self.entries.swap(self.selected, self.selected - 1);
+            self.selected -= 1;
+        }
+    }
+
+    /// Moves the selected column one place later.
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.entries.len() {
+            self.entries.swap(self.selected, self.selected + 1);
+            self.selected += 1;
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let items = self.entries.iter().map(|&(column, shown)| {
+            let check = if shown { "[x] " } else { "[ ] " };
+            ListItem::new(Line::from(vec![
+                Span::raw(check),
+                Span::raw(format!("{:<18}", column.title())),
+                Span::styled(column.description(), theme.dim),
+            ]))
+        });
+        let list = List::new(items)
+            .block(
+                theme
+                    .block("Columns")
+                    .title_bottom(" Space show/hide  J/K move  Esc done "),
+            )
+            .highlight_style(theme.selected);
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(
+            list,
+            area,
+            &mut ListState::default().with_selected(Some(self.selected)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::Column::*;
+
+    #[test]
+    fn fixed_columns_fit_their_content_and_title() {
+        // CPU% needs its title and the indicator; USER is capped.
+        let widths = fit_widths(&[Pid, Cpu, User], &[7, 3, 30], 100);
+        assert_eq!(widths, [7, 6, 12]);
+    }
+
+    #[test]
+    fn flexible_columns_share_what_is_left() {
+        // 40 left after the PID and two spaces.
+        let widths = fit_widths(&[Pid, Name, Command], &[5, 50, 50], 47);
+        assert_eq!(widths, [5, 20, 20]);
+    }
+
+    #[test]
+    fn narrow_flexible_columns_leave_room_for_wide_ones() {
+        let widths = fit_widths(&[Pid, Name, Command], &[5, 10, 100], 67);
+        assert_eq!(widths, [5, 10, 50]);
+    }
+
+    #[test]
+    fn flexible_columns_stop_at_their_content() {
+        let widths = fit_widths(&[Name, Command], &[8, 12], 200);
+        assert_eq!(widths, [8, 12]);
+    }
+
+    #[test]
+    fn fixed_columns_are_not_squeezed() {
+        let widths = fit_widths(&[Pid, Memory, Name], &[7, 9, 30], 10);
+        assert_eq!(widths, [7, 9, 0]);
+    }
+
+    #[test]
+    fn drops_nothing_when_everything_fits() {
+        let shown = drop_to_fit(&[Pid, Name, Cpu], &[5, 20, 5], 100, Cpu);
+        assert_eq!(shown, [0, 1, 2]);
+    }
+
+    #[test]
+    fn drops_the_lowest_priority_first() {
+        // Needs 5, 16 (Name at its minimum), 6, 4 and 16, plus four spaces: 51.
+        let columns = [Pid, Name, Cpu, Nice, Cwd];
+        let content = [5, 20, 5, 3, 30];
+        assert_eq!(drop_to_fit(&columns, &content, 51, Cpu), [0, 1, 2, 3, 4]);
+        assert_eq!(drop_to_fit(&columns, &content, 50, Cpu), [0, 1, 2, 3]);
+        assert_eq!(drop_to_fit(&columns, &content, 30, Cpu), [0, 1, 2]);
+    }
+
+    #[test]
+    fn drops_the_rightmost_of_equals() {
+        let shown = drop_to_fit(&[DiskRead, DiskWrite], &[5, 5], 12, Pid);
+        assert_eq!(shown, [0]);
+    }
+
+    #[test]
+    fn never_drops_the_sort_column() {
+        let columns = [Pid, Name, Cpu, Nice, Cwd];
+        let shown = drop_to_fit(&columns, &[5, 20, 5, 3, 30], 30, Cwd);
+        assert_eq!(shown, [0, 4]);
+    }
+
+    #[test]
+    fn keeps_one_column_however_narrow() {
+        assert_eq!(drop_to_fit(&[Pid, Cpu], &[7, 5], 3, Cpu), [1]);
+    }
+}
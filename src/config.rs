@@ -0,0 +1,337 @@
+//! Settings kept between runs, in `$XDG_CONFIG_HOME/vtop/config.toml`.
+//!
+//! Every field is optional in the file; anything left out takes its default. Unknown keys are
+//! rejected rather than ignored so a typo doesn't silently do nothing.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use color_eyre::{
+    Result, Section,
+    eyre::{WrapErr, eyre},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{DEFAULT_INTERVAL, MAX_INTERVAL, MIN_INTERVAL};
+use crate::history;
+use crate::keymap::Keymap;
+use crate::process::{Column, DEFAULT_COLUMNS, Sort};
+use crate::theme::Theme;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Seconds between samples.
+    pub interval: f64,
+    pub sort: Sort,
+    /// The process table's columns, in display order.
+    pub columns: Vec<Column>,
+    /// Whether the process table starts as a tree.
+    pub tree: bool,
+    pub panels: Panels,
+    /// The color theme: a built-in one, or one from `themes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// User themes, as theme name to element name to style.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub themes: BTreeMap<String, BTreeMap<String, String>>,
+    /// The preset key bindings start from: `default`, `vim` or `htop`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keymap: Option<String>,
+    /// Key bindings on top of the preset, as action name to space-separated keys.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL.as_secs_f64(),
+            sort: Sort::default(),
+            columns: DEFAULT_COLUMNS.to_vec(),
+            tree: false,
+            panels: Panels::default(),
+            theme: None,
+            themes: BTreeMap::new(),
+            keymap: None,
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+/// Which optional parts of the screen are shown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Panels {
+    pub graphs: bool,
+    /// Seconds of history the graphs cover; one of the windows `w` cycles through.
+    pub graph_window: u64,
+    pub network: bool,
+    /// Leave loopback and virtual interfaces out of the network panel.
+    pub hide_virtual: bool,
+    pub disks: bool,
+    pub sensors: bool,
+    /// The hottest CPU sensor in the header.
+    pub cpu_temperature: bool,
+    /// Clock frequencies on the CPU meters.
+    pub frequency: bool,
+}
+
+impl Default for Panels {
+    fn default() -> Self {
+        Self {
+            graphs: false,
+            graph_window: history::WINDOWS[0].as_secs(),
+            network: false,
+            hide_virtual: false,
+            disks: false,
+            sensors: false,
+            cpu_temperature: false,
+            frequency: false,
+        }
+    }
+}
+
+impl Config {
+    /// Where the config file lives: under `$XDG_CONFIG_HOME`, or `~/.config` if that isn't set.
+    pub fn path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("vtop").join("config.toml"))
+    }
+
+    /// Reads the config file, or returns the defaults if there isn't one.
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("could not read {}", path.display()));
+            }
+        };
+        // toml's errors already point at the line and column.
+        let config: Self = toml::from_str(&text)
+            .map_err(|err| eyre!("{} is malformed\n{err}", path.display()))
+            .suggestion("fix the file or delete it to go back to the defaults")?;
+        config
+            .validate()
+            .map_err(|message| eyre!("{} is malformed: {message}", path.display()))
+            .suggestion("fix the file or delete it to go back to the defaults")?;
+        Ok(config)
+    }
+
+    /// Writes the config file, creating its directory if needed, and returns where it went.
+    pub fn save(&self) -> Result<PathBuf> {
+        let path = Self::path().ok_or_else(|| {
+            eyre!("neither XDG_CONFIG_HOME nor HOME is set, so there's nowhere to save to")
+        })?;
+        self.save_to(&path)?;
+        Ok(path)
+    }
+
+    fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("could not create {}", dir.display()))?;
+        }
+        let text = toml::to_string_pretty(self).wrap_err("could not serialize the settings")?;
+        fs::write(path, text).wrap_err_with(|| format!("could not write {}", path.display()))
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs_f64(self.interval)
+    }
+
+    pub fn graph_window(&self) -> Duration {
+        Duration::from_secs(self.panels.graph_window)
+    }
+
+    /// The color theme, before it's adapted to the terminal.
+    pub fn theme(&self) -> Result<Theme> {
+        Theme::load(self.theme.as_deref().unwrap_or("default"), &self.themes)
+    }
+
+    /// The key bindings, or why they can't be used.
+    pub fn keymap(&self) -> Result<Keymap> {
+        let preset = self.keymap.as_deref().unwrap_or("default");
+        Keymap::new(preset, &self.keys)
+    }
+
+    /// Checks what the types alone don't.
+    fn validate(&self) -> Result<(), String> {
+        let interval = Duration::try_from_secs_f64(self.interval)
+            .map_err(|_| format!("interval `{}` is not a number of seconds", self.interval))?;
+        if !(MIN_INTERVAL..=MAX_INTERVAL).contains(&interval) {
+            return Err(format!(
+                "interval must be between {} and {} seconds",
+                MIN_INTERVAL.as_secs_f64(),
+                MAX_INTERVAL.as_secs_f64()
+            ));
+        }
+        if self.columns.is_empty() {
+            return Err("columns must list at least one column".to_string());
+        }
+        for (index, column) in self.columns.iter().enumerate() {
+            if self.columns[..index].contains(column) {
+                return Err(format!(
+                    "columns lists the {} column more than once",
+                    column.title()
+                ));
+            }
+        }
+        if !history::WINDOWS.contains(&self.graph_window()) {
+            let windows: Vec<_> = history::WINDOWS
+                .iter()
+                .map(|window| window.as_secs().to_string())
+                .collect();
+            return Err(format!(
+                "graph_window must be one of {} seconds",
+                windows.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Config {
+        toml::from_str(text).unwrap()
+    }
+
+    fn problem(text: &str) -> String {
+        parse(text).validate().unwrap_err()
+    }
+
+    /// A file under the temp directory, in a directory of its own that's removed on drop.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("vtop-{}-{name}", std::process::id()));
+            Self(dir.join("config.toml"))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            if let Some(dir) = self.0.parent() {
+                let _ = fs::remove_dir_all(dir);
+            }
+        }
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert_eq!(Config::default().validate(), Ok(()));
+        assert_eq!(parse("").validate(), Ok(()));
+    }
+
+    #[test]
+    fn sort_direction_defaults_by_column() {
+        assert_eq!(
+            parse("[sort]\ncolumn = \"cpu\"").sort,
+            Sort::by(Column::Cpu)
+        );
+        assert_eq!(
+            parse("[sort]\ncolumn = \"pid\"").sort,
+            Sort::by(Column::Pid)
+        );
+        let sort = parse("[sort]\ndescending = false").sort;
+        assert_eq!(sort.column, Sort::default().column);
+        assert!(!sort.descending);
+        assert!(toml::from_str::<Config>("[sort]\ncolum = \"cpu\"").is_err());
+    }
+
+    #[test]
+    fn validate_checks_the_interval() {
+        assert_eq!(parse("interval = 0.25").validate(), Ok(()));
+        assert!(problem("interval = 0.1").contains("between 0.25 and 60 seconds"));
+        assert!(problem("interval = 90").contains("between 0.25 and 60 seconds"));
+        assert!(problem("interval = -1").contains("is not a number of seconds"));
+    }
+
+    #[test]
+    fn validate_checks_the_columns() {
+        assert_eq!(
+            problem("columns = []"),
+            "columns must list at least one column"
+        );
+        assert_eq!(
+            problem("columns = [\"pid\", \"cpu\", \"pid\"]"),
+            "columns lists the PID column more than once"
+        );
+    }
+
+    #[test]
+    fn validate_checks_the_graph_window() {
+        let window = history::WINDOWS[0].as_secs();
+        assert_eq!(
+            parse(&format!("[panels]\ngraph_window = {window}")).validate(),
+            Ok(())
+        );
+        assert!(problem("[panels]\ngraph_window = 7").starts_with("graph_window must be one of"));
+    }
+
+    #[test]
+    fn saved_settings_load_back() {
+        let file = TempFile::new("round-trip");
+        let mut config = Config {
+            interval: 2.5,
+            sort: Sort::by(Column::Pid).reversed(),
+            columns: vec![Column::Pid, Column::User, Column::Command],
+            tree: true,
+            theme: Some("solarized".to_string()),
+            keymap: Some("vim".to_string()),
+            ..Config::default()
+        };
+        config.panels.network = true;
+        config.panels.graph_window = history::WINDOWS[1].as_secs();
+        config.keys.insert("quit".to_string(), "Q F10".to_string());
+        config.themes.insert(
+            "mine".to_string(),
+            [("base".to_string(), "light".to_string())].into(),
+        );
+
+        config.save_to(&file.0).unwrap();
+        let loaded = Config::load_from(&file.0).unwrap();
+        assert_eq!(
+            toml::to_string(&loaded).unwrap(),
+            toml::to_string(&config).unwrap()
+        );
+    }
+
+    #[test]
+    fn missing_file_gives_defaults() {
+        let file = TempFile::new("missing");
+        let loaded = Config::load_from(&file.0).unwrap();
+        assert_eq!(
+            toml::to_string(&loaded).unwrap(),
+            toml::to_string(&Config::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn malformed_file_is_an_error() {
+        let file = TempFile::new("malformed");
+        fs::create_dir_all(file.0.parent().unwrap()).unwrap();
+        fs::write(&file.0, "interval = \"fast\"").unwrap();
+        assert!(Config::load_from(&file.0).is_err());
+        fs::write(&file.0, "columns = []").unwrap();
+        assert!(Config::load_from(&file.0).is_err());
+    }
+}
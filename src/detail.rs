@@ -0,0 +1,366 @@
+//! The detail screen for a single process.
+//!
+//! Opening it starts recording that process's CPU and memory use, so its graphs cover the time
+//! since it was opened. If the process exits while it's open, the last snapshot stays up.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, TimeZone};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Axis, Chart, Paragraph, Widget, Wrap},
+};
+use sysinfo::{Gid, Groups, Pid, Process, System, Users};
+
+use crate::format::{self, ByteUnits};
+use crate::graphs;
+use crate::history::Series;
+use crate::scroll::ScrollingText;
+use crate::theme::Theme;
+
+/// Lines given to the CPU and memory graphs, borders included.
+const GRAPH_LINES: u16 = 10;
+
+/// Everything shown about the process, copied out of sysinfo at the last sample.
+#[derive(Debug, Default)]
+struct Snapshot {
+    name: String,
+    command: String,
+    environment: Vec<String>,
+    exe: String,
+    cwd: String,
+    root: String,
+    user: String,
+    effective_user: String,
+    group: String,
+    start_time: u64,
+    run_time: u64,
+    status: String,
+    threads: Option<usize>,
+    cpu: f32,
+    memory: u64,
+    virtual_memory: u64,
+    /// From the parent up to the root, each as `(pid, name)`.
+    ancestors: Vec<(Pid, String)>,
+    children: Vec<(Pid, String)>,
+}
+
+impl Snapshot {
+    fn new(process: &Process, system: &System, users: &Users, groups: &Groups) -> Self {
+        let path = |path: Option<&Path>| {
+            path.map_or_else(|| "-".to_string(), |path| path.display().to_string())
+        };
+        let user_name = |uid| {
+            users
+                .get_user_by_id(uid)
+                .map_or_else(|| "-".to_string(), |user| user.name().to_string())
+        };
+        // sysinfo has no lookup by ID for groups; a host only has a handful.
+        let group_name = |gid: Gid| {
+            groups
+                .iter()
+                .find(|group| *group.id() == gid)
+                .map_or_else(|| (*gid).to_string(), |group| group.name().to_string())
+        };
+        let named =
+            |process: &Process| (process.pid(), process.name().to_string_lossy().into_owned());
+
+        let mut ancestors = Vec::new();
+        let mut parent = process.parent();
+        // PID reuse could in principle make a loop, so don't follow more links than there are
+        // processes.
+        while let Some(process) = parent.and_then(|pid| system.process(pid))
+            && ancestors.len() < system.processes().len()
+        {
+            ancestors.push(named(process));
+            parent = process.parent();
+        }
+        let mut children: Vec<_> = system
+            .processes()
+            .values()
+            .filter(|child| child.parent() == Some(process.pid()) && child.thread_kind().is_none())
+            .map(named)
+            .collect();
+        children.sort_by_key(|&(pid, _)| pid);
+
+        Self {
+            name: process.name().to_string_lossy().into_owned(),
+            command: process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" "),
+            environment: process
+                .environ()
+                .iter()
+                .map(|variable| variable.to_string_lossy().into_owned())
+                .collect(),
+            exe: path(process.exe()),
+            cwd: path(process.cwd()),
+            root: path(process.root()),
+            user: process.user_id().map_or_else(|| "-".to_string(), user_name),
+            effective_user: process
+                .effective_user_id()
+                .map_or_else(|| "-".to_string(), user_name),
+            group: process
+                .group_id()
+                .map_or_else(|| "-".to_string(), group_name),
+            start_time: process.start_time(),
+            run_time: process.run_time(),
+            status: process.status().to_string(),
+            threads: process.tasks().map(|tasks| tasks.len()),
+            cpu: process.cpu_usage(),
+            memory: process.memory(),
+            virtual_memory: process.virtual_memory(),
+            ancestors,
+            children,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProcessDetail {
+    pid: Pid,
+    snapshot: Snapshot,
+    exited: bool,
+    cpu: Series,
+    memory: Series,
+    /// How far the information is scrolled, in lines.
+    scroll: u16,
+    /// How far it can be scrolled before the last line leaves the bottom, as of the last
+    /// [`ProcessDetail::fit`].
+    max_scroll: u16,
+}
+
+impl ProcessDetail {
+    pub fn new(pid: Pid) -> Self {
+        Self {
+            pid,
+            snapshot: Snapshot::default(),
+            exited: false,
+            cpu: Series::default(),
+            memory: Series::default(),
+            scroll: 0,
+            max_scroll: 0,
+        }
+    }
+
+    pub fn pid(&self) -> Pid {
+        self.pid
+    }
+
+    /// Takes a new snapshot of the process, and records its usage at `at` for the graphs.
+    pub fn update(&mut self, system: &System, users: &Users, groups: &Groups, at: Instant) {
+        let Some(process) = system.process(self.pid) else {
+            self.exited = true;
+            return;
+        };
+        self.snapshot = Snapshot::new(process, system, users, groups);
+        self.cpu.push(at, self.snapshot.cpu as f64);
+        self.memory.push(at, self.snapshot.memory as f64);
+    }
+
+    /// Works out how far the information can scroll when the view is drawn in `area`. Long
+    /// lines wrap, so this depends on the width as well as the number of lines.
+    pub fn fit(&mut self, area: Rect, units: ByteUnits, theme: &Theme) {
+        let (info_area, _) = areas(area, theme);
+        let height = self.paragraph(units, theme).line_count(info_area.width);
+        self.max_scroll = (height as u16).saturating_sub(info_area.height);
+        self.scroll = self.scroll.min(self.max_scroll);
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll = 0;
+    }
+
+    fn paragraph(&self, units: ByteUnits, theme: &Theme) -> Paragraph<'static> {
+        Paragraph::new(self.lines(units, theme)).wrap(Wrap { trim: false })
+    }
+
+    fn lines(&self, units: ByteUnits, theme: &Theme) -> Vec<Line<'static>> {
+        let snapshot = &self.snapshot;
+        let field = |name: &str, value: String| {
+            Line::from(vec![
+                Span::styled(format!("{name:<14}"), theme.label),
+                Span::raw(value),
+            ])
+        };
+        let started = Local
+            .timestamp_opt(snapshot.start_time as i64, 0)
+            .single()
+            .map_or_else(
+                || "-".to_string(),
+                |time| time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            );
+        let list = |processes: &[(Pid, String)], separator: &str| {
+            if processes.is_empty() {
+                return "-".to_string();
+            }
+            processes
+                .iter()
+                .map(|(pid, name)| format!("{name} ({pid})"))
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        let heading = |text: &str| Line::from(Span::styled(text.to_string(), theme.heading));
+
+        let mut lines = vec![
+            field("Command", snapshot.command.clone()),
+            field("Executable", snapshot.exe.clone()),
+            field("Working dir", snapshot.cwd.clone()),
+            field("Root", snapshot.root.clone()),
+            field(
+                "User",
+                format!("{} (effective {})", snapshot.user, snapshot.effective_user),
+            ),
+            field("Group", snapshot.group.clone()),
+            field("Started", started),
+            field("Running for", format::uptime(snapshot.run_time)),
+            field("Status", snapshot.status.clone()),
+            field(
+                "Threads",
+                snapshot
+                    .threads
+                    .map_or_else(|| "-".to_string(), |threads| threads.to_string()),
+            ),
+            field(
+                "Memory",
+                format!(
+                    "{} resident, {} virtual",
+                    format::bytes(snapshot.memory, units),
+                    format::bytes(snapshot.virtual_memory, units)
+                ),
+            ),
+            field("Parents", list(&snapshot.ancestors, " < ")),
+            field("Children", list(&snapshot.children, ", ")),
+            Line::from(""),
+            heading("Environment"),
+        ];
+        if snapshot.environment.is_empty() {
+            lines.push(Line::styled("(none, or not readable)", theme.dim));
+        }
+        lines.extend(
+            snapshot
+                .environment
+                .iter()
+                .map(|variable| Line::from(variable.clone())),
+        );
+        lines
+    }
+}
+
+impl ScrollingText for ProcessDetail {
+    fn scroll_down(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_add(lines).min(self.max_scroll);
+    }
+
+    fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+}
+
+/// The detail screen: process information on top, usage graphs below.
+#[derive(Debug)]
+pub struct DetailView<'a> {
+    detail: &'a ProcessDetail,
+    units: ByteUnits,
+    window: Duration,
+    now: Instant,
+    theme: &'a Theme,
+}
+
+impl<'a> DetailView<'a> {
+    pub fn new(
+        detail: &'a ProcessDetail,
+        units: ByteUnits,
+        window: Duration,
+        now: Instant,
+        theme: &'a Theme,
+    ) -> Self {
+        Self {
+            detail,
+            units,
+            window,
+            now,
+            theme,
+        }
+    }
+
+    fn chart<'b>(
+        &self,
+        title: &str,
+        datasets: Vec<ratatui::widgets::Dataset<'b>>,
+        top: f64,
+        label: String,
+    ) -> Chart<'b> {
+        let window = self.window.as_secs_f64();
+        Chart::new(datasets)
+            .block(
+                self.theme
+                    .block(format!("{title} (last {})", format::window(self.window))),
+            )
+            .x_axis(Axis::default().bounds([-window, 0.0]))
+            .y_axis(
+                Axis::default()
+                    .bounds([0.0, top])
+                    .labels(["0".to_string(), label]),
+            )
+    }
+}
+
+impl Widget for DetailView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let detail = self.detail;
+        let mut title = format!("{} ({})", detail.snapshot.name, detail.pid);
+        if detail.exited {
+            title.push_str(" - exited");
+        }
+        self.theme
+            .block(title)
+            .title_bottom(" Esc back  j/k scroll ")
+            .render(area, buf);
+
+        let (info_area, graphs_area) = areas(area, self.theme);
+        detail
+            .paragraph(self.units, self.theme)
+            .scroll((detail.scroll, 0))
+            .render(info_area, buf);
+
+        let [cpu_area, memory_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(graphs_area);
+
+        let cpu = detail.cpu.points(self.now, self.window);
+        // Percent of one core, so a busy multi-threaded process goes past 100.
+        let top = cpu.iter().map(|&(_, usage)| usage).fold(100.0, f64::max);
+        self.chart(
+            "CPU",
+            vec![graphs::line(&cpu, self.theme.cpu_line)],
+            top,
+            format!("{top:.0}%"),
+        )
+        .render(cpu_area, buf);
+
+        let memory = detail.memory.points(self.now, self.window);
+        let top = memory.iter().map(|&(_, bytes)| bytes).fold(1.0, f64::max);
+        self.chart(
+            "Memory",
+            vec![graphs::line(&memory, self.theme.memory_line)],
+            top,
+            format::bytes(top as u64, self.units),
+        )
+        .render(memory_area, buf);
+    }
+}
+
+/// Where the information and the graphs go when the view is drawn in `area`.
+fn areas(area: Rect, theme: &Theme) -> (Rect, Rect) {
+    let inner = theme.block("").inner(area);
+    let [info_area, graphs_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(GRAPH_LINES)]).areas(inner);
+    (info_area, graphs_area)
+}
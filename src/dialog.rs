@@ -0,0 +1,130 @@
+//! Modal popups drawn over the main view.
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    text::Line,
+    widgets::{Clear, List, ListState, Paragraph, Wrap},
+};
+use sysinfo::{Pid, Signal};
+
+use crate::columns::ColumnSetup;
+use crate::help::Help;
+use crate::process::Column;
+use crate::theme::Theme;
+
+/// The signals offered by the kill dialog, in the order they're listed.
+pub const SIGNALS: [Signal; 8] = [
+    Signal::Term,
+    Signal::Kill,
+    Signal::Hangup,
+    Signal::Interrupt,
+    Signal::Stop,
+    Signal::Continue,
+    Signal::User1,
+    Signal::User2,
+];
+
+/// The conventional `SIG*` name, which is what people look for in a list of signals.
+pub fn signal_name(signal: Signal) -> &'static str {
+    match signal {
+        Signal::Term => "SIGTERM",
+        Signal::Kill => "SIGKILL",
+        Signal::Hangup => "SIGHUP",
+        Signal::Interrupt => "SIGINT",
+        Signal::Stop => "SIGSTOP",
+        Signal::Continue => "SIGCONT",
+        Signal::User1 => "SIGUSR1",
+        Signal::User2 => "SIGUSR2",
+        _ => "signal",
+    }
+}
+
+/// The process a dialog acts on. The name is kept so the dialog can still say what it's about if
+/// the process exits while it's open, and with the start time it tells the process apart from a
+/// new one that has been given the same PID since.
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub pid: Pid,
+    pub name: String,
+    /// In seconds since the epoch.
+    pub start_time: u64,
+}
+
+impl Target {
+    pub fn describe(&self) -> String {
+        format!("{} ({})", self.name, self.pid)
+    }
+}
+
+#[derive(Debug)]
+pub enum Dialog {
+    /// Choosing which signal to send. `selected` indexes [`SIGNALS`].
+    SignalPicker { target: Target, selected: usize },
+    /// Last chance to back out before the signal is sent.
+    ConfirmSignal { target: Target, signal: Signal },
+    /// Choosing and ordering the process table's columns.
+    ColumnSetup(ColumnSetup),
+    /// The list of key bindings.
+    Help(Help),
+}
+
+impl Dialog {
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        match self {
+            Self::SignalPicker { target, selected } => {
+                let area = popup_area(frame.area(), 36, SIGNALS.len() as u16 + 2);
+                let list = List::new(SIGNALS.map(signal_name))
+                    .block(theme.block(format!("Send signal to {}", target.describe())))
+                    .highlight_style(theme.selected);
+                frame.render_widget(Clear, area);
+                frame.render_stateful_widget(
+                    list,
+                    area,
+                    &mut ListState::default().with_selected(Some(*selected)),
+                );
+            }
+            Self::ConfirmSignal { target, signal } => {
+                let area = popup_area(frame.area(), 44, 5);
+                let text = vec![
+                    Line::from(format!(
+                        "Send {} to {}?",
+                        signal_name(*signal),
+                        target.describe()
+                    )),
+                    Line::from(""),
+                    Line::from("[y] Yes    [n] No"),
+                ];
+                let paragraph = Paragraph::new(text)
+                    .wrap(Wrap { trim: true })
+                    .block(theme.block("Confirm"));
+                frame.render_widget(Clear, area);
+                frame.render_widget(paragraph, area);
+            }
+            Self::ColumnSetup(setup) => {
+                let height = Column::ALL.len() as u16 + 2;
+                setup.render(frame, popup_area(frame.area(), 80, height), theme);
+            }
+            Self::Help(help) => {
+                // As tall as the screen allows; it scrolls for the rest.
+                help.render(
+                    frame,
+                    popup_area(frame.area(), 64, help.height() + 2),
+                    theme,
+                );
+            }
+        }
+    }
+}
+
+/// A `width` x `height` rectangle centered in `area`, shrunk to fit if necessary.
+fn popup_area(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
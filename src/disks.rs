@@ -0,0 +1,131 @@
+//! The disks panel: one line per mounted filesystem with how full it is.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    text::Line,
+    widgets::{LineGauge, Paragraph, Widget},
+};
+use sysinfo::Disks;
+
+use crate::format::{self, ByteUnits};
+use crate::meters;
+use crate::scroll::{self, PanelView, ScrollingPanel};
+use crate::theme::Theme;
+
+const MOUNT_WIDTH: u16 = 24;
+const FILE_SYSTEM_WIDTH: u16 = 10;
+const FLAGS_WIDTH: u16 = 13;
+
+#[derive(Debug)]
+pub struct DisksPanel<'a> {
+    disks: &'a Disks,
+    units: ByteUnits,
+    theme: &'a Theme,
+    view: PanelView,
+}
+
+impl<'a> DisksPanel<'a> {
+    pub fn new(disks: &'a Disks, units: ByteUnits, theme: &'a Theme) -> Self {
+        Self {
+            disks,
+            units,
+            theme,
+            view: PanelView::default(),
+        }
+    }
+
+    /// The height needed to list every disk, within limits.
+    pub fn height(&self) -> u16 {
+        scroll::panel_height(self.disks.list().len())
+    }
+
+    fn columns(area: Rect) -> [Rect; 4] {
+        Layout::horizontal([
+            Constraint::Length(MOUNT_WIDTH),
+            Constraint::Length(FILE_SYSTEM_WIDTH),
+            Constraint::Min(0),
+            Constraint::Length(FLAGS_WIDTH),
+        ])
+        .spacing(1)
+        .areas(area)
+    }
+}
+
+impl ScrollingPanel for DisksPanel<'_> {
+    fn view_mut(&mut self) -> &mut PanelView {
+        &mut self.view
+    }
+}
+
+impl Widget for DisksPanel<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let theme = self.theme;
+        let block = theme.panel_block("Disks", self.view.focused);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height == 0 {
+            return;
+        }
+
+        let header = theme.table_header;
+        let [mount, file_system, usage, flags] = Self::columns(inner);
+        Paragraph::new("Mounted on")
+            .style(header)
+            .render(mount, buf);
+        Paragraph::new("Type")
+            .style(header)
+            .render(file_system, buf);
+        Paragraph::new("Used / Size")
+            .style(header)
+            .render(usage, buf);
+        Paragraph::new("Flags").style(header).render(flags, buf);
+
+        for (line, disk) in (1..inner.height).zip(self.disks.list().iter().skip(self.view.scroll)) {
+            let row = Rect {
+                y: inner.y + line,
+                height: 1,
+                ..inner
+            };
+            let [mount, file_system, usage, flags] = Self::columns(row);
+
+            // Long mount points keep their tail, which is the part that tells them apart.
+            let path = disk.mount_point().to_string_lossy();
+            let skip = path.chars().count().saturating_sub(mount.width as usize);
+            let path: String = path.chars().skip(skip).collect();
+            Paragraph::new(path).render(mount, buf);
+            Paragraph::new(disk.file_system().to_string_lossy().into_owned())
+                .render(file_system, buf);
+
+            let total = disk.total_space();
+            let used = total.saturating_sub(disk.available_space());
+            let ratio = if total == 0 {
+                0.0
+            } else {
+                used as f64 / total as f64
+            };
+            LineGauge::default()
+                .label(format!(
+                    "{:>21} ",
+                    format!(
+                        "{} / {}",
+                        format::bytes(used, self.units),
+                        format::bytes(total, self.units)
+                    )
+                ))
+                .ratio(ratio.clamp(0.0, 1.0))
+                .filled_style(meters::load_style((ratio * 100.0) as f32, theme))
+                .unfilled_style(theme.dim)
+                .render(usage, buf);
+
+            let mut tags = Vec::new();
+            if disk.is_removable() {
+                tags.push("removable");
+            }
+            if disk.is_read_only() {
+                tags.push("ro");
+            }
+            Line::from(tags.join(",")).render(flags, buf);
+        }
+    }
+}
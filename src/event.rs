@@ -0,0 +1,105 @@
+//! The app's event source.
+//!
+//! Terminal input is read on a dedicated thread and forwarded over a channel, so the main loop
+//! can block on "next input or next sample, whichever comes first" instead of alternating between
+//! polling and sleeping. Keys are handled as soon as they arrive, while samples keep their own
+//! steady cadence.
+
+use std::io;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use color_eyre::{Result, eyre::eyre};
+use crossterm::event::{self, Event as CrosstermEvent};
+
+#[derive(Debug)]
+pub enum Event {
+    /// The sample interval has elapsed; time to refresh system data.
+    Tick,
+    /// Input or a resize from the terminal.
+    Crossterm(CrosstermEvent),
+}
+
+#[derive(Debug)]
+pub struct EventHandler {
+    receiver: mpsc::Receiver<io::Result<CrosstermEvent>>,
+    /// An event read while collapsing a run of resizes, to be returned next.
+    pending: Option<io::Result<CrosstermEvent>>,
+    tick_rate: Duration,
+    next_tick: Instant,
+}
+
+impl EventHandler {
+    /// Spawns the input thread. The first [`Event::Tick`] fires one `tick_rate` from now.
+    pub fn new(tick_rate: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            loop {
+                let event = event::read();
+                let failed = event.is_err();
+                if sender.send(event).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Self {
+            receiver,
+            pending: None,
+            tick_rate,
+            next_tick: Instant::now() + tick_rate,
+        }
+    }
+
+    pub fn tick_rate(&self) -> Duration {
+        self.tick_rate
+    }
+
+    /// Changes the tick rate. The next tick is rescheduled relative to the previous one, so
+    /// speeding up takes effect immediately rather than after the old interval runs out.
+    pub fn set_tick_rate(&mut self, tick_rate: Duration) {
+        self.next_tick = self.next_tick - self.tick_rate + tick_rate;
+        self.tick_rate = tick_rate;
+    }
+
+    /// Blocks until the next terminal event or tick.
+    ///
+    /// Dragging a window's edge sends a resize for every step; only the last of those already
+    /// waiting is returned, so the screen is redrawn once at the final size rather than at each
+    /// one in between.
+    pub fn next(&mut self) -> Result<Event> {
+        // A due tick goes first, or a steady stream of input (a held key, the mouse moving)
+        // would hold off sampling for as long as it lasts.
+        if Instant::now() >= self.next_tick {
+            return Ok(self.tick());
+        }
+        if let Some(event) = self.pending.take() {
+            return Ok(Event::Crossterm(event?));
+        }
+        let timeout = self.next_tick.saturating_duration_since(Instant::now());
+        match self.receiver.recv_timeout(timeout) {
+            Ok(Ok(mut event @ CrosstermEvent::Resize(..))) => {
+                while let Ok(next) = self.receiver.try_recv() {
+                    match next {
+                        Ok(resize @ CrosstermEvent::Resize(..)) => event = resize,
+                        other => {
+                            self.pending = Some(other);
+                            break;
+                        }
+                    }
+                }
+                Ok(Event::Crossterm(event))
+            }
+            Ok(event) => Ok(Event::Crossterm(event?)),
+            Err(mpsc::RecvTimeoutError::Timeout) => Ok(self.tick()),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(eyre!("terminal input thread exited")),
+        }
+    }
+
+    fn tick(&mut self) -> Event {
+        // Schedule from now rather than from the missed deadline so a slow sample (or a
+        // suspended process) doesn't cause a burst of catch-up ticks.
+        self.next_tick = Instant::now() + self.tick_rate;
+        Event::Tick
+    }
+}
@@ -0,0 +1,159 @@
+//! Turning raw numbers into text for display.
+
+use std::time::Duration;
+
+use chrono::{Datelike, Local, TimeZone};
+
+/// Which family of prefixes [`bytes`] scales with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ByteUnits {
+    /// Powers of 1024: KiB, MiB, GiB, ...
+    #[default]
+    Binary,
+    /// Powers of 1000: kB, MB, GB, ...
+    Si,
+}
+
+impl ByteUnits {
+    fn base(self) -> f64 {
+        match self {
+            Self::Binary => 1024.0,
+            Self::Si => 1000.0,
+        }
+    }
+
+    fn prefixes(self) -> &'static [&'static str] {
+        match self {
+            Self::Binary => &["KiB", "MiB", "GiB", "TiB", "PiB"],
+            Self::Si => &["kB", "MB", "GB", "TB", "PB"],
+        }
+    }
+}
+
+/// Formats a byte count with the largest unit that keeps the value at or above 1, e.g.
+/// `1536` becomes `"1.5 KiB"`. Counts below one unit are printed exactly: `"512 B"`.
+pub fn bytes(count: u64, units: ByteUnits) -> String {
+    let base = units.base();
+    let mut value = count as f64;
+    if value < base {
+        return format!("{count} B");
+    }
+    let mut prefix = "B";
+    for &next in units.prefixes() {
+        // Compared as printed, so a value just short of the next unit shows as `1.0 MiB` rather
+        // than `1024.0 KiB`.
+        if (value * 10.0).round() / 10.0 < base {
+            break;
+        }
+        value /= base;
+        prefix = next;
+    }
+    format!("{value:.1} {prefix}")
+}
+
+/// Formats CPU time in milliseconds like top's `TIME+`: `m:ss.hh` under an hour, `h:mm:ss`
+/// beyond that.
+pub fn cpu_time(millis: u64) -> String {
+    let secs = millis / 1000;
+    if secs < 3600 {
+        let hundredths = millis % 1000 / 10;
+        format!("{}:{:02}.{hundredths:02}", secs / 60, secs % 60)
+    } else {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    }
+}
+
+/// Formats an uptime in seconds like top: `3 days, 4:12` or, under a day, `4:12:09`.
+pub fn uptime(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60);
+    match days {
+        0 => format!("{hours}:{minutes:02}:{:02}", secs % 60),
+        1 => format!("1 day, {hours}:{minutes:02}"),
+        _ => format!("{days} days, {hours}:{minutes:02}"),
+    }
+}
+
+/// Formats a span of time as whole minutes (`2m`) when it divides evenly, or seconds (`90s`).
+pub fn window(span: Duration) -> String {
+    let secs = span.as_secs();
+    if secs >= 60 && secs.is_multiple_of(60) {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Formats a clock frequency given in MHz as GHz with one decimal, e.g. `3.4GHz`.
+pub fn frequency(mhz: u64) -> String {
+    format!("{:.1}GHz", mhz as f64 / 1000.0)
+}
+
+/// Formats a throughput in bytes per second like [`bytes`], e.g. `"1.5 KiB/s"`.
+pub fn rate(bytes_per_sec: f64, units: ByteUnits) -> String {
+    format!("{}/s", bytes(bytes_per_sec.round() as u64, units))
+}
+
+/// Formats a temperature in degrees Celsius, e.g. `54°C`, or a dash when the sensor has no
+/// reading.
+pub fn temperature(celsius: Option<f32>) -> String {
+    match celsius {
+        Some(celsius) if celsius.is_finite() => format!("{celsius:.0}°C"),
+        _ => "-".to_string(),
+    }
+}
+
+/// Formats a start time in seconds since the epoch like `ps`'s `STIME`: the time of day if it
+/// was today, the date if it was this year, otherwise just the year.
+pub fn start_time(epoch_secs: u64) -> String {
+    let Some(start) = Local.timestamp_opt(epoch_secs as i64, 0).single() else {
+        return "-".to_string();
+    };
+    let now = Local::now();
+    let pattern = if start.date_naive() == now.date_naive() {
+        "%H:%M"
+    } else if start.year() == now.year() {
+        "%b%d"
+    } else {
+        "%Y"
+    };
+    start.format(pattern).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_below_one_unit_are_exact() {
+        assert_eq!(bytes(0, ByteUnits::Binary), "0 B");
+        assert_eq!(bytes(1023, ByteUnits::Binary), "1023 B");
+        assert_eq!(bytes(999, ByteUnits::Si), "999 B");
+    }
+
+    #[test]
+    fn bytes_scale_to_the_largest_unit() {
+        assert_eq!(bytes(1024, ByteUnits::Binary), "1.0 KiB");
+        assert_eq!(bytes(1536, ByteUnits::Binary), "1.5 KiB");
+        assert_eq!(bytes(5 << 30, ByteUnits::Binary), "5.0 GiB");
+        assert_eq!(bytes(1000, ByteUnits::Si), "1.0 kB");
+        assert_eq!(bytes(2_500_000, ByteUnits::Si), "2.5 MB");
+    }
+
+    #[test]
+    fn bytes_move_up_a_unit_when_rounding_reaches_it() {
+        assert_eq!(bytes(1_048_575, ByteUnits::Binary), "1.0 MiB");
+        assert_eq!(bytes(1_048_524, ByteUnits::Binary), "1023.9 KiB");
+        assert_eq!(bytes(999_999, ByteUnits::Si), "1.0 MB");
+    }
+
+    #[test]
+    fn bytes_stop_at_the_largest_prefix() {
+        assert_eq!(bytes(u64::MAX, ByteUnits::Binary), "16384.0 PiB");
+    }
+
+    #[test]
+    fn rate_rounds_to_whole_bytes() {
+        assert_eq!(rate(0.4, ByteUnits::Binary), "0 B/s");
+        assert_eq!(rate(1536.0, ByteUnits::Binary), "1.5 KiB/s");
+    }
+}
@@ -0,0 +1,104 @@
+//! Line charts of CPU and memory usage over time.
+
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    symbols::Marker,
+    widgets::{Axis, Chart, Dataset, GraphType, LegendPosition, Widget},
+};
+
+use crate::format;
+use crate::history::{History, Series};
+use crate::theme::Theme;
+
+/// Above this many cores the per-core lines are just noise, so only the total is drawn.
+const MAX_CORE_LINES: usize = 16;
+
+/// CPU on the left, memory and swap on the right.
+#[derive(Debug)]
+pub struct HistoryGraphs<'a> {
+    history: &'a History,
+    window: Duration,
+    now: Instant,
+    theme: &'a Theme,
+}
+
+impl<'a> HistoryGraphs<'a> {
+    pub fn new(history: &'a History, window: Duration, now: Instant, theme: &'a Theme) -> Self {
+        Self {
+            history,
+            window,
+            now,
+            theme,
+        }
+    }
+
+    fn points(&self, series: &Series) -> Vec<(f64, f64)> {
+        series.points(self.now, self.window)
+    }
+}
+
+impl Widget for HistoryGraphs<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [cpu_area, memory_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(area);
+
+        let total = self.points(&self.history.cpu);
+        let cores: Vec<_> = if self.history.cores.len() <= MAX_CORE_LINES {
+            self.history
+                .cores
+                .iter()
+                .map(|series| self.points(series))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        // Cores first so the total is drawn on top of them.
+        let mut datasets: Vec<_> = cores
+            .iter()
+            .map(|points| line(points, self.theme.core_line))
+            .collect();
+        datasets.push(line(&total, self.theme.cpu_line).name("total"));
+        self.chart("CPU", datasets).render(cpu_area, buf);
+
+        let memory = self.points(&self.history.memory);
+        let swap = self.points(&self.history.swap);
+        let datasets = vec![
+            line(&swap, self.theme.swap_line).name("swap"),
+            line(&memory, self.theme.memory_line).name("memory"),
+        ];
+        self.chart("Memory", datasets).render(memory_area, buf);
+    }
+}
+
+impl HistoryGraphs<'_> {
+    fn chart<'a>(&self, title: &'a str, datasets: Vec<Dataset<'a>>) -> Chart<'a> {
+        let window = self.window.as_secs_f64();
+        Chart::new(datasets)
+            .block(
+                self.theme
+                    .block(format!("{title} (last {})", format::window(self.window))),
+            )
+            .x_axis(Axis::default().bounds([-window, 0.0]))
+            .y_axis(
+                Axis::default()
+                    .bounds([0.0, 100.0])
+                    .labels(["0%", "50%", "100%"]),
+            )
+            .legend_position(Some(LegendPosition::TopLeft))
+            .hidden_legend_constraints((Constraint::Percentage(50), Constraint::Percentage(50)))
+    }
+}
+
+/// A braille line through `points`.
+pub fn line(points: &[(f64, f64)], style: Style) -> Dataset<'_> {
+    Dataset::default()
+        .data(points)
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(style)
+}
@@ -0,0 +1,232 @@
+//! The system summary at the top of the screen, in the spirit of top's header.
+
+use std::time::Duration;
+
+use chrono::{Local, TimeZone};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{LineGauge, Paragraph, Widget},
+};
+use sysinfo::System;
+
+use crate::format::{self, ByteUnits};
+use crate::meters::{self, CpuMeters};
+use crate::process::TaskCounts;
+use crate::sensors;
+use crate::theme::Theme;
+
+/// Gauges for CPU, memory and swap, then the tasks line and the load/uptime line.
+const SUMMARY_LINES: u16 = 5;
+
+/// The collapsed header: one line of gauges between the borders.
+const COLLAPSED_HEIGHT: u16 = 3;
+
+/// Gauge values are right-aligned to this width so the gauges line up.
+const VALUE_WIDTH: usize = 24;
+
+#[derive(Debug)]
+pub struct Header<'a> {
+    system: &'a System,
+    tasks: TaskCounts,
+    units: ByteUnits,
+    interval: Duration,
+    meters: CpuMeters<'a>,
+    /// Whether to show the CPU temperature at all, and if so the hottest CPU sensor's reading
+    /// and critical temperature, if there is one.
+    show_cpu_temperature: bool,
+    cpu_temperature: Option<(f32, Option<f32>)>,
+    /// Just the CPU, memory and swap gauges, side by side, for short terminals.
+    collapsed: bool,
+    theme: &'a Theme,
+}
+
+impl<'a> Header<'a> {
+    pub fn new(
+        system: &'a System,
+        tasks: TaskCounts,
+        units: ByteUnits,
+        interval: Duration,
+        meters: CpuMeters<'a>,
+        theme: &'a Theme,
+    ) -> Self {
+        Self {
+            system,
+            tasks,
+            units,
+            interval,
+            meters,
+            show_cpu_temperature: false,
+            cpu_temperature: None,
+            collapsed: false,
+            theme,
+        }
+    }
+
+    pub fn cpu_temperature(mut self, reading: Option<(f32, Option<f32>)>) -> Self {
+        self.show_cpu_temperature = true;
+        self.cpu_temperature = reading;
+        self
+    }
+
+    pub fn collapsed(mut self, collapsed: bool) -> Self {
+        self.collapsed = collapsed;
+        self
+    }
+
+    /// The lines the per-core meters may use when the header is allowed `max_height` in total.
+    pub fn meter_budget(max_height: u16) -> u16 {
+        max_height.saturating_sub(SUMMARY_LINES + 2)
+    }
+
+    /// The height needed at `width`, borders included.
+    pub fn height(&self, width: u16) -> u16 {
+        if self.collapsed {
+            return COLLAPSED_HEIGHT;
+        }
+        SUMMARY_LINES + self.meters.height(width.saturating_sub(2)) + 2
+    }
+
+    fn cpu_gauge(&self) -> LineGauge<'static> {
+        let usage = self.system.global_cpu_usage();
+        let value = format!("{usage:.1}%");
+        usage_gauge(self.label("CPU", value), usage as f64 / 100.0, self.theme)
+    }
+
+    fn gauge(&self, name: &str, used: u64, total: u64) -> LineGauge<'static> {
+        let ratio = if total == 0 {
+            0.0
+        } else {
+            (used as f64 / total as f64).clamp(0.0, 1.0)
+        };
+        let value = format!(
+            "{} / {}",
+            format::bytes(used, self.units),
+            format::bytes(total, self.units)
+        );
+        usage_gauge(self.label(name, value), ratio, self.theme)
+    }
+
+    /// A gauge's label. Stacked gauges pad their values so they line up; collapsed ones are
+    /// short of room, so don't.
+    fn label(&self, name: &str, value: String) -> String {
+        if self.collapsed {
+            format!("{name} {value} ")
+        } else {
+            format!("{name:<4}{value:>VALUE_WIDTH$} ")
+        }
+    }
+
+    fn tasks_line(&self) -> Line<'static> {
+        let tasks = self.tasks;
+        let available = self.system.available_memory();
+        // sysinfo doesn't report the page cache directly; what's available beyond the truly free
+        // memory is, near enough, cache the kernel can reclaim.
+        let cache = available.saturating_sub(self.system.free_memory());
+        let label = self.theme.label;
+        Line::from(vec![
+            Span::styled("Tasks: ", label),
+            Span::raw(format!(
+                "{} total, {} running, {} sleeping, {} stopped, ",
+                tasks.total, tasks.running, tasks.sleeping, tasks.stopped
+            )),
+            Span::styled(
+                format!("{} zombie", tasks.zombie),
+                if tasks.zombie > 0 {
+                    self.theme.error
+                } else {
+                    Style::default()
+                },
+            ),
+            Span::styled("   Avail: ", label),
+            Span::raw(format::bytes(available, self.units)),
+            Span::styled("   Cache: ", label),
+            Span::raw(format::bytes(cache, self.units)),
+        ])
+    }
+
+    fn uptime_line(&self) -> Line<'static> {
+        let load = System::load_average();
+        let booted = Local
+            .timestamp_opt(System::boot_time() as i64, 0)
+            .single()
+            .map_or_else(
+                || "unknown".to_string(),
+                |time| time.format("%Y-%m-%d %H:%M").to_string(),
+            );
+        let label = self.theme.label;
+        let mut spans = vec![
+            Span::styled("Load average: ", label),
+            Span::raw(format!(
+                "{:.2} {:.2} {:.2}",
+                load.one, load.five, load.fifteen
+            )),
+            Span::styled("   Uptime: ", label),
+            Span::raw(format::uptime(System::uptime())),
+            Span::styled("   Booted: ", label),
+            Span::raw(booted),
+            Span::styled("   Refresh: ", label),
+            Span::raw(format!("{:.2}s", self.interval.as_secs_f64())),
+        ];
+        if self.show_cpu_temperature {
+            spans.push(Span::styled("   CPU temp: ", label));
+            spans.push(match self.cpu_temperature {
+                Some((celsius, critical)) => Span::styled(
+                    format::temperature(Some(celsius)),
+                    sensors::temperature_style(celsius, critical, self.theme),
+                ),
+                None => Span::raw(format::temperature(None)),
+            });
+        }
+        Line::from(spans)
+    }
+}
+
+impl Widget for Header<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = self.theme.block("System Info");
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let memory_gauge = self.gauge("Mem", self.system.used_memory(), self.system.total_memory());
+        let swap_gauge = self.gauge("Swp", self.system.used_swap(), self.system.total_swap());
+        if self.collapsed {
+            let [cpu, memory, swap] = Layout::horizontal([Constraint::Fill(1); 3])
+                .spacing(2)
+                .areas(inner);
+            self.cpu_gauge().render(cpu, buf);
+            memory_gauge.render(memory, buf);
+            swap_gauge.render(swap, buf);
+            return;
+        }
+
+        let [cpu, memory, swap, tasks, uptime, meters] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .areas(inner);
+
+        self.cpu_gauge().render(cpu, buf);
+        memory_gauge.render(memory, buf);
+        swap_gauge.render(swap, buf);
+        Paragraph::new(self.tasks_line()).render(tasks, buf);
+        Paragraph::new(self.uptime_line()).render(uptime, buf);
+        self.meters.render(meters, buf);
+    }
+}
+
+/// A one-line gauge with `label` before it, colored by how full it is.
+fn usage_gauge(label: String, ratio: f64, theme: &Theme) -> LineGauge<'static> {
+    let ratio = ratio.clamp(0.0, 1.0);
+    LineGauge::default()
+        .label(label)
+        .ratio(ratio)
+        .filled_style(meters::load_style((ratio * 100.0) as f32, theme))
+        .unfilled_style(theme.dim)
+}
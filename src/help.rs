@@ -0,0 +1,175 @@
+//! The help popup, listing what every key does, and the shortcut bar along the bottom.
+
+use ratatui::{
+    Frame,
+    buffer::Buffer,
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Clear, Paragraph, Widget},
+};
+
+use crate::keymap::{Action, Group, Keymap};
+use crate::scroll::ScrollingText;
+use crate::theme::Theme;
+
+/// Width given to the keys before each description.
+const KEYS_WIDTH: usize = 20;
+
+/// Keys that belong to a screen or dialog rather than the keymap, so can't be rebound.
+const FIXED_KEYS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Search bar",
+        &[
+            ("Enter", "Keep the query and leave the bar"),
+            ("Esc", "Clear the query and leave the bar"),
+            ("Tab", "Switch between filter and search"),
+            ("Ctrl-r", "Ignore case, match case or regex"),
+            ("Ctrl-u", "Clear the query"),
+        ],
+    ),
+    (
+        "Dialogs",
+        &[
+            ("j k Up Down", "Move the selection"),
+            ("Enter", "Choose"),
+            ("y n", "Answer a confirmation"),
+            ("Space", "Show or hide a column"),
+            ("J K", "Move a column"),
+            ("Esc q", "Close"),
+        ],
+    ),
+    (
+        "Process details",
+        &[
+            ("j k Up Down", "Scroll"),
+            ("PgDn PgUp", "Scroll a page"),
+            ("g Home", "Back to the top"),
+            ("Esc q Enter", "Back to the table"),
+        ],
+    ),
+    (
+        "Mouse",
+        &[
+            ("Click", "Select a process, or focus a panel"),
+            ("Click a title", "Sort by that column, again to reverse"),
+            ("Wheel", "Scroll"),
+        ],
+    ),
+    ("Anywhere", &[("Ctrl-c", "Quit")]),
+];
+
+/// The help popup. Its text is built from the keymap when it's opened.
+#[derive(Debug, Clone)]
+pub struct Help {
+    lines: Vec<Line<'static>>,
+    /// How far the text is scrolled, in lines.
+    scroll: u16,
+}
+
+impl Help {
+    pub fn new(keymap: &Keymap, theme: &Theme) -> Self {
+        let heading = |text: &str| Line::from(Span::styled(text.to_string(), theme.heading));
+        let entry = |keys: String, description: &str| {
+            Line::from(vec![
+                Span::styled(format!("{keys:<KEYS_WIDTH$}"), theme.key),
+                Span::raw(description.to_string()),
+            ])
+        };
+
+        let mut lines = Vec::new();
+        for group in Group::ALL {
+            lines.push(heading(group.title()));
+            for action in Action::ALL {
+                if action.group() != group {
+                    continue;
+                }
+                let keys: Vec<String> = keymap.keys(action).map(|key| key.to_string()).collect();
+                // Unbound actions are left out rather than listed with no key.
+                if !keys.is_empty() {
+                    lines.push(entry(keys.join(" "), action.description()));
+                }
+            }
+            lines.push(Line::from(""));
+        }
+        for (title, keys) in FIXED_KEYS {
+            lines.push(heading(title));
+            for (keys, description) in *keys {
+                lines.push(entry(keys.to_string(), description));
+            }
+            lines.push(Line::from(""));
+        }
+        lines.pop();
+        Self { lines, scroll: 0 }
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll = 0;
+    }
+
+    /// Lines of text, for sizing the popup.
+    pub fn height(&self) -> u16 {
+        self.lines.len() as u16
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let paragraph = Paragraph::new(self.lines.clone())
+            .scroll((self.scroll, 0))
+            .block(theme.block("Keys").title_bottom(" j/k scroll  Esc close "));
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+    }
+}
+
+impl ScrollingText for Help {
+    fn scroll_down(&mut self, lines: u16) {
+        let last = self.lines.len().saturating_sub(1) as u16;
+        self.scroll = self.scroll.saturating_add(lines).min(last);
+    }
+
+    fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+}
+
+/// The shortcut bar: a key, then what it does, for each of the keys that matter most on the
+/// current screen.
+#[derive(Debug)]
+pub struct Footer<'a> {
+    entries: Vec<(String, &'static str)>,
+    theme: &'a Theme,
+}
+
+impl<'a> Footer<'a> {
+    pub fn new(entries: Vec<(String, &'static str)>, theme: &'a Theme) -> Self {
+        Self { entries, theme }
+    }
+
+    /// The first key bound to each of `actions`, labelled. Unbound actions are left out.
+    pub fn from_keymap(
+        keymap: &Keymap,
+        actions: &[(Action, &'static str)],
+        theme: &'a Theme,
+    ) -> Self {
+        let entries = actions
+            .iter()
+            .filter_map(|&(action, label)| {
+                let key = keymap.keys(action).next()?;
+                Some((key.to_string(), label))
+            })
+            .collect();
+        Self { entries, theme }
+    }
+}
+
+impl Widget for Footer<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let key_style = self.theme.key.add_modifier(Modifier::REVERSED);
+        let mut spans = Vec::new();
+        for (key, label) in self.entries {
+            spans.push(Span::styled(key, key_style));
+            spans.push(Span::raw(format!(" {label}  ")));
+        }
+        Line::from(spans).render(area, buf);
+    }
+}
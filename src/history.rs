@@ -0,0 +1,83 @@
+//! Recent samples kept for the history graphs.
+//!
+//! Each series is a ring buffer of timestamped values trimmed by age rather than by count, so the
+//! graphs cover the same span of time whatever the sample interval is.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use sysinfo::System;
+
+/// The longest window the graphs can show, and so how much history is kept.
+pub const MAX_WINDOW: Duration = Duration::from_secs(600);
+
+/// The windows `w` cycles through.
+pub const WINDOWS: [Duration; 4] = [
+    Duration::from_secs(60),
+    Duration::from_secs(120),
+    Duration::from_secs(300),
+    MAX_WINDOW,
+];
+
+#[derive(Debug, Default)]
+pub struct Series {
+    samples: VecDeque<(Instant, f64)>,
+}
+
+impl Series {
+    pub fn push(&mut self, at: Instant, value: f64) {
+        self.samples.push_back((at, value));
+        while self
+            .samples
+            .front()
+            .is_some_and(|&(time, _)| at.duration_since(time) > MAX_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// The samples from the last `window` as `(seconds before now, value)` pairs, oldest first,
+    /// ready for a [`ratatui::widgets::Chart`] whose x axis runs from `-window` to 0.
+    pub fn points(&self, now: Instant, window: Duration) -> Vec<(f64, f64)> {
+        self.samples
+            .iter()
+            .filter(|&&(time, _)| now.duration_since(time) <= window)
+            .map(|&(time, value)| (-now.duration_since(time).as_secs_f64(), value))
+            .collect()
+    }
+}
+
+/// System-wide usage history, all in percent.
+#[derive(Debug, Default)]
+pub struct History {
+    pub cpu: Series,
+    pub cores: Vec<Series>,
+    pub memory: Series,
+    pub swap: Series,
+}
+
+impl History {
+    /// Appends the current values from `system`.
+    pub fn record(&mut self, system: &System, at: Instant) {
+        self.cpu.push(at, system.global_cpu_usage() as f64);
+
+        let cpus = system.cpus();
+        self.cores.resize_with(cpus.len(), Series::default);
+        for (series, cpu) in self.cores.iter_mut().zip(cpus) {
+            series.push(at, cpu.cpu_usage() as f64);
+        }
+
+        self.memory
+            .push(at, percent(system.used_memory(), system.total_memory()));
+        self.swap
+            .push(at, percent(system.used_swap(), system.total_swap()));
+    }
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 / total as f64 * 100.0
+    }
+}
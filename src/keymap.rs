@@ -0,0 +1,720 @@
+//! Key bindings for the main screen: what each key does, in one of a few presets, with the
+//! user's own bindings from the config file on top.
+//!
+//! Dialogs, the search bar and the detail screen keep their own fixed keys, and Ctrl-C always
+//! quits.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use color_eyre::{Result, Section, eyre::eyre};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Help,
+    /// Moves keyboard focus to the next panel on screen.
+    FocusNext,
+    /// Clears the search query; only while one is in effect.
+    ClearSearch,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    ToggleTree,
+    Expand,
+    Collapse,
+    SlowDown,
+    SpeedUp,
+    ToggleFrequency,
+    ToggleGraphs,
+    CycleGraphWindow,
+    ToggleNetwork,
+    ToggleVirtual,
+    ToggleDisks,
+    ToggleSensors,
+    ToggleCpuTemperature,
+    SortPrevious,
+    SortNext,
+    SortByCpu,
+    SortByMemory,
+    SortByPid,
+    SortByTime,
+    ReverseSort,
+    Down,
+    Up,
+    PageDown,
+    PageUp,
+    First,
+    Last,
+    Kill,
+    Details,
+    ColumnSetup,
+    SaveConfig,
+}
+
+/// When an action applies. A key may be bound once per scope; where several of its actions
+/// apply, the narrowest scope wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Scope {
+    Always,
+    /// The process table is a tree.
+    Tree,
+    /// A search query is in effect.
+    Search,
+    /// A query is in effect in search mode, so there are matches to jump between.
+    Matches,
+}
+
+/// The sections of the help.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Group {
+    Global,
+    Table,
+    Search,
+}
+
+impl Group {
+    pub const ALL: [Group; 3] = [Group::Global, Group::Table, Group::Search];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Group::Global => "Global",
+            Group::Table => "Process table",
+            Group::Search => "Search",
+        }
+    }
+}
+
+impl Action {
+    pub const ALL: [Action; 37] = [
+        Action::Quit,
+        Action::Help,
+        Action::FocusNext,
+        Action::ClearSearch,
+        Action::Search,
+        Action::NextMatch,
+        Action::PreviousMatch,
+        Action::ToggleTree,
+        Action::Expand,
+        Action::Collapse,
+        Action::SlowDown,
+        Action::SpeedUp,
+        Action::ToggleFrequency,
+        Action::ToggleGraphs,
+        Action::CycleGraphWindow,
+        Action::ToggleNetwork,
+        Action::ToggleVirtual,
+        Action::ToggleDisks,
+        Action::ToggleSensors,
+        Action::ToggleCpuTemperature,
+        Action::SortPrevious,
+        Action::SortNext,
+        Action::SortByCpu,
+        Action::SortByMemory,
+        Action::SortByPid,
+        Action::SortByTime,
+        Action::ReverseSort,
+        Action::Down,
+        Action::Up,
+        Action::PageDown,
+        Action::PageUp,
+        Action::First,
+        Action::Last,
+        Action::Kill,
+        Action::Details,
+        Action::ColumnSetup,
+        Action::SaveConfig,
+    ];
+
+    /// The name used for the action in the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Help => "help",
+            Action::FocusNext => "focus_next",
+            Action::ClearSearch => "clear_search",
+            Action::Search => "search",
+            Action::NextMatch => "next_match",
+            Action::PreviousMatch => "previous_match",
+            Action::ToggleTree => "toggle_tree",
+            Action::Expand => "expand",
+            Action::Collapse => "collapse",
+            Action::SlowDown => "slow_down",
+            Action::SpeedUp => "speed_up",
+            Action::ToggleFrequency => "toggle_frequency",
+            Action::ToggleGraphs => "toggle_graphs",
+            Action::CycleGraphWindow => "cycle_graph_window",
+            Action::ToggleNetwork => "toggle_network",
+            Action::ToggleVirtual => "toggle_virtual",
+            Action::ToggleDisks => "toggle_disks",
+            Action::ToggleSensors => "toggle_sensors",
+            Action::ToggleCpuTemperature => "toggle_cpu_temperature",
+            Action::SortPrevious => "sort_previous",
+            Action::SortNext => "sort_next",
+            Action::SortByCpu => "sort_by_cpu",
+            Action::SortByMemory => "sort_by_memory",
+            Action::SortByPid => "sort_by_pid",
+            Action::SortByTime => "sort_by_time",
+            Action::ReverseSort => "reverse_sort",
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::PageDown => "page_down",
+            Action::PageUp => "page_up",
+            Action::First => "first",
+            Action::Last => "last",
+            Action::Kill => "kill",
+            Action::Details => "details",
+            Action::ColumnSetup => "column_setup",
+            Action::SaveConfig => "save_config",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// What the action does, in a few words.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Help => "Show this help",
+            Action::FocusNext => "Move focus to the next panel",
+            Action::ClearSearch => "Clear the search",
+            Action::Search => "Search or filter processes",
+            Action::NextMatch => "Next match",
+            Action::PreviousMatch => "Previous match",
+            Action::ToggleTree => "Tree view on/off",
+            Action::Expand => "Expand the selected branch",
+            Action::Collapse => "Collapse the selected branch",
+            Action::SlowDown => "Sample less often",
+            Action::SpeedUp => "Sample more often",
+            Action::ToggleFrequency => "CPU frequencies on/off",
+            Action::ToggleGraphs => "History graphs on/off",
+            Action::CycleGraphWindow => "Change how far back the graphs go",
+            Action::ToggleNetwork => "Network panel on/off",
+            Action::ToggleVirtual => "Virtual interfaces on/off",
+            Action::ToggleDisks => "Disks panel on/off",
+            Action::ToggleSensors => "Sensors panel on/off",
+            Action::ToggleCpuTemperature => "CPU temperature on/off",
+            Action::SortPrevious => "Sort by the column to the left",
+            Action::SortNext => "Sort by the column to the right",
+            Action::SortByCpu => "Sort by CPU",
+            Action::SortByMemory => "Sort by memory",
+            Action::SortByPid => "Sort by PID",
+            Action::SortByTime => "Sort by CPU time",
+            Action::ReverseSort => "Reverse the sort order",
+            Action::Down => "Down a row",
+            Action::Up => "Up a row",
+            Action::PageDown => "Page down",
+            Action::PageUp => "Page up",
+            Action::First => "To the top",
+            Action::Last => "To the bottom",
+            Action::Kill => "Send a signal",
+            Action::Details => "Process details",
+            Action::ColumnSetup => "Choose columns",
+            Action::SaveConfig => "Save settings",
+        }
+    }
+
+    /// Where the action is listed in the help.
+    pub fn group(self) -> Group {
+        match self {
+            Action::Search | Action::ClearSearch | Action::NextMatch | Action::PreviousMatch => {
+                Group::Search
+            }
+            Action::ToggleTree
+            | Action::Expand
+            | Action::Collapse
+            | Action::SortPrevious
+            | Action::SortNext
+            | Action::SortByCpu
+            | Action::SortByMemory
+            | Action::SortByPid
+            | Action::SortByTime
+            | Action::ReverseSort
+            | Action::Down
+            | Action::Up
+            | Action::PageDown
+            | Action::PageUp
+            | Action::First
+            | Action::Last
+            | Action::Kill
+            | Action::Details => Group::Table,
+            _ => Group::Global,
+        }
+    }
+
+    pub fn scope(self) -> Scope {
+        match self {
+            Action::Expand | Action::Collapse => Scope::Tree,
+            Action::ClearSearch => Scope::Search,
+            Action::NextMatch | Action::PreviousMatch => Scope::Matches,
+            _ => Scope::Always,
+        }
+    }
+}
+
+/// A key together with the modifiers held with it.
+///
+/// Shift isn't kept for characters, since it's already in the character itself: `N` rather
+/// than Shift-n.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        // Shift is already in the character, or in BackTab, which crossterm reports with Shift
+        // held.
+        if let KeyCode::Char(_) | KeyCode::BackTab = code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    /// Parses a key as written in the config file: a character such as `q` or `+`, or a key
+    /// name such as `F5`, `Enter` or `PageDown`, optionally after `Ctrl-`, `Alt-` or `Shift-`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        // The key itself may be `-`, so only split off prefixes that are known modifiers.
+        while let Some((prefix, key)) = rest.split_once('-')
+            && !key.is_empty()
+        {
+            let modifier = match prefix.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => break,
+            };
+            modifiers |= modifier;
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "insert" => KeyCode::Insert,
+                "delete" | "del" => KeyCode::Delete,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=24) => KeyCode::F(n),
+                    _ => return Err(format!("`{text}` is not a key")),
+                },
+            },
+        };
+        // Ctrl-N and Ctrl-n are the same key to a terminal. Otherwise Shift is written the way
+        // the terminal sends it: as the shifted character, and Shift-Tab as BackTab.
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            KeyCode::Char(c) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+            KeyCode::Tab if shift => KeyCode::BackTab,
+            code => code,
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        let code = match key.code {
+            KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            code => code,
+        };
+        Self::new(code, key.modifiers)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("Alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("Shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::Esc => f.write_str("Esc"),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            KeyCode::Delete => f.write_str("Del"),
+            KeyCode::Insert => f.write_str("Insert"),
+            KeyCode::Enter => f.write_str("Enter"),
+            KeyCode::Tab => f.write_str("Tab"),
+            KeyCode::BackTab => f.write_str("Shift-Tab"),
+            KeyCode::Backspace => f.write_str("Backspace"),
+            KeyCode::Up => f.write_str("Up"),
+            KeyCode::Down => f.write_str("Down"),
+            KeyCode::Left => f.write_str("Left"),
+            KeyCode::Right => f.write_str("Right"),
+            KeyCode::Home => f.write_str("Home"),
+            KeyCode::End => f.write_str("End"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// The bindings every preset starts from: a mix of htop's letters and vi's movement keys.
+const DEFAULT_BINDINGS: &[(Action, &[&str])] = &[
+    (Action::Quit, &["q", "Esc"]),
+    (Action::Help, &["?", "F1"]),
+    (Action::FocusNext, &["Tab"]),
+    (Action::ClearSearch, &["Esc"]),
+    (Action::Search, &["/"]),
+    (Action::NextMatch, &["n"]),
+    (Action::PreviousMatch, &["N"]),
+    (Action::ToggleTree, &["t", "F5"]),
+    // In the tree view `+` and `-` expand and collapse, as in htop.
+    (Action::Expand, &["+", "Right", "l"]),
+    (Action::Collapse, &["-", "Left", "h"]),
+    (Action::SlowDown, &["+"]),
+    (Action::SpeedUp, &["-"]),
+    (Action::ToggleFrequency, &["f"]),
+    (Action::ToggleGraphs, &["v"]),
+    (Action::CycleGraphWindow, &["w"]),
+    (Action::ToggleNetwork, &["i"]),
+    (Action::ToggleVirtual, &["V"]),
+    (Action::ToggleDisks, &["d"]),
+    (Action::ToggleSensors, &["s"]),
+    (Action::ToggleCpuTemperature, &["S"]),
+    (Action::SortPrevious, &["<"]),
+    (Action::SortNext, &[">"]),
+    (Action::SortByCpu, &["P"]),
+    (Action::SortByMemory, &["M"]),
+    // `N` steps back through search matches when there are any.
+    (Action::SortByPid, &["N"]),
+    (Action::SortByTime, &["T"]),
+    (Action::ReverseSort, &["I", "r"]),
+    (Action::Down, &["Down", "j"]),
+    (Action::Up, &["Up", "k"]),
+    (Action::PageDown, &["PageDown"]),
+    (Action::PageUp, &["PageUp"]),
+    (Action::First, &["Home", "g"]),
+    (Action::Last, &["End", "G"]),
+    (Action::Kill, &["F9", "K"]),
+    (Action::Details, &["Enter"]),
+    (Action::ColumnSetup, &["F2", "C"]),
+    (Action::SaveConfig, &["W"]),
+];
+
+/// What the vim preset changes: half and full pages on the Ctrl keys.
+const VIM_BINDINGS: &[(Action, &[&str])] = &[
+    (Action::PageDown, &["PageDown", "Ctrl-d", "Ctrl-f"]),
+    (Action::PageUp, &["PageUp", "Ctrl-u", "Ctrl-b"]),
+];
+
+/// What the htop preset changes: htop's function keys, and no vi movement.
+const HTOP_BINDINGS: &[(Action, &[&str])] = &[
+    (Action::Quit, &["q", "F10", "Esc"]),
+    (Action::Help, &["F1", "h", "?"]),
+    (Action::Search, &["/", "F3", "F4"]),
+    (Action::ToggleTree, &["t", "F5"]),
+    (Action::Expand, &["+"]),
+    (Action::Collapse, &["-"]),
+    (Action::SortNext, &[">", "F6"]),
+    (Action::Down, &["Down"]),
+    (Action::Up, &["Up"]),
+    (Action::First, &["Home"]),
+    (Action::Last, &["End"]),
+    (Action::Kill, &["F9", "k"]),
+    (Action::ColumnSetup, &["F2", "C"]),
+];
+
+/// Named sets of bindings to start from, chosen with `keymap` in the config file.
+pub const PRESETS: [&str; 3] = ["default", "vim", "htop"];
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    /// Every binding, in the order they were made.
+    bindings: Vec<(KeyChord, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new("default", &BTreeMap::new()).expect("the default keymap is valid")
+    }
+}
+
+impl Keymap {
+    /// Builds the keymap for `preset`, with `overrides` (action name to space-separated keys)
+    /// replacing the preset's keys for those actions. An empty list unbinds the action.
+    ///
+    /// Unknown actions, keys that can't be parsed and keys bound twice in the same scope are
+    /// all errors, so a mistake in the config file is caught at startup.
+    pub fn new(preset: &str, overrides: &BTreeMap<String, String>) -> Result<Self> {
+        let changes = match preset {
+            "default" => &[][..],
+            "vim" => VIM_BINDINGS,
+            "htop" => HTOP_BINDINGS,
+            _ => {
+                return Err(eyre!("there is no keymap called `{preset}`"))
+                    .suggestion(format!("use one of {}", PRESETS.join(", ")));
+            }
+        };
+        let mut keys: Vec<(Action, Vec<String>)> = DEFAULT_BINDINGS
+            .iter()
+            .map(|&(action, keys)| {
+                let keys = changes
+                    .iter()
+                    .find(|&&(changed, _)| changed == action)
+                    .map_or(keys, |&(_, keys)| keys);
+                (action, keys.iter().map(|key| key.to_string()).collect())
+            })
+            .collect();
+
+        let mut problems = Vec::new();
+        for (name, value) in overrides {
+            match Action::from_name(name) {
+                Some(action) => {
+                    let entry = keys
+                        .iter_mut()
+                        .find(|(bound, _)| *bound == action)
+                        .expect("every action has default bindings");
+                    entry.1 = value.split_whitespace().map(str::to_string).collect();
+                }
+                None => problems.push(format!("`{name}` is not an action")),
+            }
+        }
+
+        let mut bindings = Vec::new();
+        for (action, keys) in keys {
+            for key in keys {
+                match KeyChord::parse(&key) {
+                    Ok(chord) => bindings.push((chord, action)),
+                    Err(problem) => {
+                        problems.push(format!("{problem} (bound to {})", action.name()))
+                    }
+                }
+            }
+        }
+        let keymap = Self { bindings };
+        problems.extend(keymap.conflicts());
+
+        if problems.is_empty() {
+            Ok(keymap)
+        } else {
+            Err(eyre!(
+                "the key bindings have problems:\n  {}",
+                problems.join("\n  ")
+            ))
+            .suggestion("fix the [keys] table in the config file")
+        }
+    }
+
+    /// A line for each key bound to more than one action in the same scope.
+    fn conflicts(&self) -> Vec<String> {
+        let mut seen: HashMap<(KeyChord, Scope), Action> = HashMap::new();
+        let mut conflicts = Vec::new();
+        for &(chord, action) in &self.bindings {
+            match seen.get(&(chord, action.scope())) {
+                Some(&first) if first != action => conflicts.push(format!(
+                    "`{chord}` is bound to both {} and {}",
+                    first.name(),
+                    action.name()
+                )),
+                Some(_) => {}
+                None => {
+                    seen.insert((chord, action.scope()), action);
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// The action for `key`, given which scopes currently apply.
+    pub fn action(&self, key: KeyEvent, applies: impl Fn(Scope) -> bool) -> Option<Action> {
+        let chord = KeyChord::from(key);
+        self.bindings
+            .iter()
+            .filter(|&&(bound, action)| bound == chord && applies(action.scope()))
+            .map(|&(_, action)| action)
+            .max_by_key(|action| action.scope())
+    }
+
+    /// The keys bound to `action`, in the order they were bound.
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = KeyChord> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |&&(_, bound)| bound == action)
+            .map(|&(chord, _)| chord)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(text: &str) -> KeyChord {
+        KeyChord::parse(text).unwrap()
+    }
+
+    /// What the terminal reports for a key press.
+    fn pressed(code: KeyCode, modifiers: KeyModifiers) -> KeyChord {
+        KeyChord::from(KeyEvent::new(code, modifiers))
+    }
+
+    fn keymap(overrides: &[(&str, &str)]) -> Result<Keymap> {
+        let overrides = overrides
+            .iter()
+            .map(|&(name, keys)| (name.to_string(), keys.to_string()))
+            .collect();
+        Keymap::new("default", &overrides)
+    }
+
+    #[test]
+    fn parses_characters_and_names() {
+        assert_eq!(chord("q"), pressed(KeyCode::Char('q'), KeyModifiers::NONE));
+        assert_eq!(chord("-"), pressed(KeyCode::Char('-'), KeyModifiers::NONE));
+        assert_eq!(
+            chord("Space"),
+            pressed(KeyCode::Char(' '), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            chord("pgdn"),
+            pressed(KeyCode::PageDown, KeyModifiers::NONE)
+        );
+        assert_eq!(chord("F12"), pressed(KeyCode::F(12), KeyModifiers::NONE));
+        assert_eq!(
+            chord("Alt-Enter"),
+            pressed(KeyCode::Enter, KeyModifiers::ALT)
+        );
+    }
+
+    #[test]
+    fn parses_modifiers_as_the_terminal_sends_them() {
+        let ctrl_n = pressed(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        assert_eq!(chord("Ctrl-n"), ctrl_n);
+        assert_eq!(chord("ctrl-N"), ctrl_n);
+        assert_eq!(
+            chord("Ctrl--"),
+            pressed(KeyCode::Char('-'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            chord("Shift-n"),
+            pressed(KeyCode::Char('N'), KeyModifiers::SHIFT)
+        );
+        assert_eq!(chord("N"), pressed(KeyCode::Char('N'), KeyModifiers::SHIFT));
+        let back_tab = pressed(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(chord("Shift-Tab"), back_tab);
+        assert_eq!(chord("BackTab"), back_tab);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        for text in ["", "F0", "F25", "Hyper-x", "Ctrl-", "nope"] {
+            assert!(KeyChord::parse(text).is_err(), "{text:?} parsed");
+        }
+    }
+
+    #[test]
+    fn displays_what_parses_back() {
+        for text in [
+            "q",
+            "N",
+            "Ctrl-d",
+            "Alt-x",
+            "Space",
+            "PgDn",
+            "F5",
+            "Shift-Tab",
+            "Del",
+        ] {
+            assert_eq!(chord(text).to_string(), text);
+            assert_eq!(chord(&chord(text).to_string()), chord(text));
+        }
+    }
+
+    #[test]
+    fn presets_have_no_conflicts() {
+        for preset in PRESETS {
+            if let Err(err) = Keymap::new(preset, &BTreeMap::new()) {
+                panic!("{preset}: {err}");
+            }
+        }
+    }
+
+    #[test]
+    fn same_key_in_one_scope_conflicts() {
+        let err = keymap(&[("search", "q")]).unwrap_err().to_string();
+        assert!(
+            err.contains("`q` is bound to both quit and search"),
+            "{err}"
+        );
+
+        let keymap = Keymap {
+            bindings: vec![
+                (chord("x"), Action::Kill),
+                (chord("x"), Action::Kill),
+                (chord("y"), Action::NextMatch),
+                (chord("y"), Action::PreviousMatch),
+            ],
+        };
+        assert_eq!(
+            keymap.conflicts(),
+            ["`y` is bound to both next_match and previous_match"]
+        );
+    }
+
+    #[test]
+    fn same_key_in_different_scopes_is_fine() {
+        let keymap = keymap(&[("expand", "q")]).unwrap();
+        let q = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(q, |_| true), Some(Action::Expand));
+        assert_eq!(
+            keymap.action(q, |scope| scope == Scope::Always),
+            Some(Action::Quit)
+        );
+    }
+
+    #[test]
+    fn overrides_replace_and_unbind() {
+        let keymap = keymap(&[("kill", ""), ("help", "F12 H")]).unwrap();
+        assert_eq!(keymap.keys(Action::Kill).count(), 0);
+        let help: Vec<_> = keymap
+            .keys(Action::Help)
+            .map(|key| key.to_string())
+            .collect();
+        assert_eq!(help, ["F12", "H"]);
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let err = keymap(&[("launch", "x"), ("quit", "Ctrl-")])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`launch` is not an action"), "{err}");
+        assert!(err.contains("`Ctrl-` is not a key"), "{err}");
+        assert!(Keymap::new("emacs", &BTreeMap::new()).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Arranging the screen for the terminal's size.
+//!
+//! By default everything is stacked: the header, the panels that are turned on, then the process
+//! table. A wide terminal puts the graphs beside the header and the disks beside the sensors, so
+//! they don't leave half of each line empty; a short one collapses the header to a line of
+//! gauges so the table keeps some room.
+
+use ratatui::layout::{Constraint, Layout, Margin, Rect};
+
+/// Terminals at least this wide put panels side by side.
+const WIDE_WIDTH: u16 = 160;
+
+/// Terminals shorter than this get the collapsed header.
+const SHORT_HEIGHT: u16 = 30;
+
+/// Below this size there's no room to spare for the margin around the screen.
+const MARGIN_MIN_WIDTH: u16 = 100;
+const MARGIN_MIN_HEIGHT: u16 = 30;
+
+/// The process table keeps at least this many lines, borders included; the panels above it
+/// shrink first.
+const MIN_TABLE_LINES: u16 = 6;
+
+/// How the screen is arranged at a given terminal size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arrangement {
+    /// Panels go side by side.
+    wide: bool,
+    /// The header is collapsed.
+    short: bool,
+    margin: bool,
+}
+
+/// The heights the parts of the screen would like, with zero for those that are hidden.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Heights {
+    pub header: u16,
+    pub graphs: u16,
+    pub network: u16,
+    pub disks: u16,
+    pub sensors: u16,
+}
+
+/// Where each part of the screen goes. Hidden parts get an empty area.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Screen {
+    pub header: Rect,
+    pub graphs: Rect,
+    pub network: Rect,
+    pub disks: Rect,
+    pub sensors: Rect,
+    pub table: Rect,
+    pub status: Rect,
+    pub footer: Rect,
+}
+
+impl Arrangement {
+    /// The arrangement for a terminal of size `area`.
+    pub fn new(area: Rect) -> Self {
+        Self {
+            wide: area.width >= WIDE_WIDTH,
+            short: area.height < SHORT_HEIGHT,
+            margin: area.width >= MARGIN_MIN_WIDTH && area.height >= MARGIN_MIN_HEIGHT,
+        }
+    }
+
+    pub fn collapsed_header(self) -> bool {
+        self.short
+    }
+
+    /// What's left of `area` once the margin is taken off.
+    pub fn inner(self, area: Rect) -> Rect {
+        if self.margin {
+            area.inner(Margin::new(1, 1))
+        } else {
+            area
+        }
+    }
+
+    /// The width the header gets in `inner`: half of it when it shares a row with the graphs.
+    pub fn header_width(self, inner: Rect, graphs: bool) -> u16 {
+        if self.wide && graphs {
+            Self::halves(inner)[0].width
+        } else {
+            inner.width
+        }
+    }
+
+    /// Lays out `inner` given the heights each part would like.
+    pub fn split(self, inner: Rect, heights: Heights) -> Screen {
+        let [body, status, footer] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+        let mut screen = Screen {
+            status,
+            footer,
+            ..Screen::default()
+        };
+
+        if !self.wide {
+            [
+                screen.header,
+                screen.graphs,
+                screen.network,
+                screen.disks,
+                screen.sensors,
+                screen.table,
+            ] = Layout::vertical([
+                Constraint::Length(heights.header),
+                Constraint::Length(heights.graphs),
+                Constraint::Length(heights.network),
+                Constraint::Length(heights.disks),
+                Constraint::Length(heights.sensors),
+                Constraint::Min(MIN_TABLE_LINES),
+            ])
+            .areas(body);
+            return screen;
+        }
+
+        let [top, network, pair, table] = Layout::vertical([
+            Constraint::Length(heights.header.max(heights.graphs)),
+            Constraint::Length(heights.network),
+            Constraint::Length(heights.disks.max(heights.sensors)),
+            Constraint::Min(MIN_TABLE_LINES),
+        ])
+        .areas(body);
+        screen.network = network;
+        screen.table = table;
+        if heights.graphs > 0 {
+            let [header, graphs] = Self::halves(top);
+            // The header keeps its own height rather than stretching to the graphs'.
+            screen.header = Rect {
+                height: header.height.min(heights.header),
+                ..header
+            };
+            screen.graphs = graphs;
+        } else {
+            screen.header = top;
+        }
+        match (heights.disks > 0, heights.sensors > 0) {
+            (true, true) => [screen.disks, screen.sensors] = Self::halves(pair),
+            (true, false) => screen.disks = pair,
+            (false, true) => screen.sensors = pair,
+            (false, false) => {}
+        }
+        screen
+    }
+
+    fn halves(area: Rect) -> [Rect; 2] {
+        Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(area)
+    }
+}
@@ -0,0 +1,174 @@
+//! Per-core CPU usage meters for the header.
+
+use ratatui::{buffer::Buffer, layout::Rect, style::Style, widgets::Widget};
+use sysinfo::Cpu;
+
+use crate::format;
+use crate::theme::Theme;
+
+/// The narrowest a full meter gets before we fit fewer columns.
+const MIN_METER_WIDTH: u16 = 24;
+
+/// Machines with at least this many cores get the compact meters regardless of space.
+pub const COMPACT_THRESHOLD: usize = 128;
+
+/// Eighth-block glyphs for compact meters, from idle to fully busy.
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The style for a usage percentage: the theme's low when there's headroom, medium when busy,
+/// high when saturated.
+pub fn load_style(percent: f32, theme: &Theme) -> Style {
+    if percent >= 80.0 {
+        theme.high
+    } else if percent >= 50.0 {
+        theme.medium
+    } else {
+        theme.low
+    }
+}
+
+/// One `N [|||||     42.0%]` meter per core, laid out in as many columns as fit and numbered
+/// down each column like htop. In compact mode each core is a single colored block instead.
+#[derive(Debug)]
+pub struct CpuMeters<'a> {
+    cpus: &'a [Cpu],
+    show_frequency: bool,
+    compact: bool,
+    theme: &'a Theme,
+}
+
+impl<'a> CpuMeters<'a> {
+    pub fn new(cpus: &'a [Cpu], theme: &'a Theme) -> Self {
+        Self {
+            cpus,
+            show_frequency: false,
+            compact: cpus.len() >= COMPACT_THRESHOLD,
+            theme,
+        }
+    }
+
+    pub fn show_frequency(mut self, show: bool) -> Self {
+        self.show_frequency = show;
+        self
+    }
+
+    /// Switches to compact meters if the full ones would need more than `max_height` lines at
+    /// `width`.
+    pub fn fit(mut self, width: u16, max_height: u16) -> Self {
+        if self.height(width) > max_height {
+            self.compact = true;
+        }
+        self
+    }
+
+    /// The number of lines needed at `width`.
+    pub fn height(&self, width: u16) -> u16 {
+        if self.cpus.is_empty() || width == 0 {
+            return 0;
+        }
+        let per_line = if self.compact {
+            width as usize
+        } else {
+            self.columns(width)
+        };
+        self.cpus.len().div_ceil(per_line) as u16
+    }
+
+    fn columns(&self, width: u16) -> usize {
+        ((width / MIN_METER_WIDTH) as usize).clamp(1, self.cpus.len().max(1))
+    }
+
+    fn render_compact(&self, area: Rect, buf: &mut Buffer) {
+        let width = area.width as usize;
+        for (index, cpu) in self.cpus.iter().enumerate() {
+            let (row, column) = (index / width, index % width);
+            if row >= area.height as usize {
+                break;
+            }
+            let usage = cpu.cpu_usage().clamp(0.0, 100.0);
+            let level = ((usage / 100.0) * (LEVELS.len() - 1) as f32).round() as usize;
+            buf[(area.x + column as u16, area.y + row as u16)]
+                .set_char(LEVELS[level])
+                .set_style(load_style(usage, self.theme));
+        }
+    }
+
+    fn render_full(&self, area: Rect, buf: &mut Buffer) {
+        let columns = self.columns(area.width);
+        let rows = self.cpus.len().div_ceil(columns);
+        let width = area.width / columns as u16;
+        let label_width = (self.cpus.len() - 1).to_string().len();
+        for (index, cpu) in self.cpus.iter().enumerate() {
+            let (row, column) = (index % rows, index / rows);
+            if row >= area.height as usize {
+                continue;
+            }
+            let meter = Rect {
+                x: area.x + column as u16 * width,
+                y: area.y + row as u16,
+                // Leave a gap between columns.
+                width: width.saturating_sub(1),
+                height: 1,
+            };
+            self.render_meter(index, cpu, label_width, meter, buf);
+        }
+    }
+
+    fn render_meter(
+        &self,
+        index: usize,
+        cpu: &Cpu,
+        label_width: usize,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let usage = cpu.cpu_usage().clamp(0.0, 100.0);
+        let label = format!("{index:>label_width$} [");
+        let mut text = format!("{usage:.1}%");
+        if self.show_frequency {
+            text = format!("{} {text}", format::frequency(cpu.frequency()));
+        }
+
+        let (x, _) = buf.set_stringn(
+            area.x,
+            area.y,
+            &label,
+            area.width as usize,
+            Style::default(),
+        );
+        let right = area.x + area.width;
+        if x + 1 >= right {
+            return;
+        }
+        // Everything between the brackets.
+        let inner = right - x - 1;
+        let filled = ((usage / 100.0) * inner as f32).round() as u16;
+        let style = load_style(usage, self.theme);
+        for offset in 0..inner {
+            let symbol = if offset < filled { "|" } else { " " };
+            buf[(x + offset, area.y)]
+                .set_symbol(symbol)
+                .set_style(style);
+        }
+        let text_width = text.chars().count() as u16;
+        if text_width <= inner {
+            buf.set_string(x + inner - text_width, area.y, &text, Style::default());
+        }
+        buf.set_string(right - 1, area.y, "]", Style::default());
+    }
+}
+
+impl Widget for CpuMeters<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Some platforms report no CPUs at all; there's nothing to draw, and no last index. An
+        // empty area has no columns to divide the CPUs between.
+        if self.cpus.is_empty() || area.width == 0 || area.height == 0 {
+            return;
+        }
+        if self.compact {
+            self.render_compact(area, buf);
+        } else {
+            self.render_full(area, buf);
+        }
+    }
+}
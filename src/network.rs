@@ -0,0 +1,296 @@
+//! Per-interface network throughput, worked out from the counters sysinfo keeps, and the panel
+//! that shows it.
+//!
+//! sysinfo reports how much each interface moved since the previous refresh; dividing by the
+//! time between refreshes gives the rate, and summing gives the totals since vtop started.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    widgets::{Axis, Chart, Row, StatefulWidget, Table, TableState, Widget},
+};
+use sysinfo::Networks;
+
+use crate::format::{self, ByteUnits};
+use crate::graphs;
+use crate::history::Series;
+use crate::scroll::{self, PanelView, ScrollingPanel};
+use crate::theme::Theme;
+
+/// Name prefixes of interfaces that don't correspond to a physical link, for platforms where
+/// we can't ask the kernel.
+const VIRTUAL_PREFIXES: [&str; 12] = [
+    "lo", "docker", "veth", "br-", "virbr", "vmnet", "vboxnet", "tun", "tap", "utun", "awdl",
+    "bridge",
+];
+
+/// Lines the throughput graph needs to be readable, borders included.
+const MIN_PANEL_LINES: u16 = 7;
+
+#[derive(Debug, Clone)]
+pub struct Interface {
+    pub name: String,
+    /// Loopback, bridges, tunnels, container veths and the like.
+    pub is_virtual: bool,
+    /// Bytes per second over the last sample.
+    pub rx_rate: f64,
+    pub tx_rate: f64,
+    /// Totals since vtop started.
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub errors: u64,
+}
+
+impl Interface {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            is_virtual: is_virtual(name),
+            rx_rate: 0.0,
+            tx_rate: 0.0,
+            rx_bytes: 0,
+            tx_bytes: 0,
+            rx_packets: 0,
+            tx_packets: 0,
+            errors: 0,
+        }
+    }
+}
+
+/// Received and transmitted bytes per second over time.
+#[derive(Debug, Default)]
+pub struct Throughput {
+    pub rx: Series,
+    pub tx: Series,
+}
+
+#[derive(Debug, Default)]
+pub struct NetworkStats {
+    last: Option<Instant>,
+    /// Sorted by name.
+    interfaces: Vec<Interface>,
+    all: Throughput,
+    /// Throughput over just the physical interfaces, for when virtual ones are hidden.
+    physical: Throughput,
+}
+
+impl NetworkStats {
+    /// Takes in the counters from a refresh of `networks` made at `at`.
+    pub fn record(&mut self, networks: &Networks, at: Instant) {
+        let elapsed = self
+            .last
+            .map(|last| at.duration_since(last).as_secs_f64())
+            .filter(|&secs| secs > 0.0);
+        self.last = Some(at);
+
+        let mut previous: HashMap<_, _> = self
+            .interfaces
+            .drain(..)
+            .map(|interface| (interface.name.clone(), interface))
+            .collect();
+        for (name, data) in networks {
+            let mut interface = previous
+                .remove(name)
+                .unwrap_or_else(|| Interface::new(name));
+            let (rx, tx) = (data.received(), data.transmitted());
+            (interface.rx_rate, interface.tx_rate) = match elapsed {
+                Some(secs) => (rx as f64 / secs, tx as f64 / secs),
+                None => (0.0, 0.0),
+            };
+            interface.rx_bytes += rx;
+            interface.tx_bytes += tx;
+            interface.rx_packets += data.packets_received();
+            interface.tx_packets += data.packets_transmitted();
+            interface.errors += data.errors_on_received() + data.errors_on_transmitted();
+            self.interfaces.push(interface);
+        }
+        self.interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // The first refresh only establishes the baseline, so there's no rate to plot yet.
+        if elapsed.is_none() {
+            return;
+        }
+        for (throughput, hide_virtual) in [(&mut self.all, false), (&mut self.physical, true)] {
+            let (rx, tx) = self
+                .interfaces
+                .iter()
+                .filter(|interface| !(hide_virtual && interface.is_virtual))
+                .fold((0.0, 0.0), |(rx, tx), interface| {
+                    (rx + interface.rx_rate, tx + interface.tx_rate)
+                });
+            throughput.rx.push(at, rx);
+            throughput.tx.push(at, tx);
+        }
+    }
+
+    pub fn interfaces(&self, hide_virtual: bool) -> impl Iterator<Item = &Interface> {
+        self.interfaces
+            .iter()
+            .filter(move |interface| !(hide_virtual && interface.is_virtual))
+    }
+
+    pub fn throughput(&self, hide_virtual: bool) -> &Throughput {
+        if hide_virtual {
+            &self.physical
+        } else {
+            &self.all
+        }
+    }
+}
+
+/// Whether `name` is a virtual interface. On Linux the kernel knows: virtual devices live under
+/// `/sys/devices/virtual`. Elsewhere we go by the usual naming conventions.
+fn is_virtual(name: &str) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(path) = std::fs::canonicalize(format!("/sys/class/net/{name}")) {
+            return path.starts_with("/sys/devices/virtual");
+        }
+    }
+    VIRTUAL_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// A table of interfaces on the left and a graph of their combined throughput on the right.
+#[derive(Debug)]
+pub struct NetworkPanel<'a> {
+    stats: &'a NetworkStats,
+    hide_virtual: bool,
+    units: ByteUnits,
+    window: Duration,
+    now: Instant,
+    theme: &'a Theme,
+    view: PanelView,
+}
+
+impl<'a> NetworkPanel<'a> {
+    pub fn new(
+        stats: &'a NetworkStats,
+        units: ByteUnits,
+        window: Duration,
+        now: Instant,
+        theme: &'a Theme,
+    ) -> Self {
+        Self {
+            stats,
+            hide_virtual: false,
+            units,
+            window,
+            now,
+            theme,
+            view: PanelView::default(),
+        }
+    }
+
+    pub fn hide_virtual(mut self, hide: bool) -> Self {
+        self.hide_virtual = hide;
+        self
+    }
+
+    /// The height needed to list every interface, within limits.
+    pub fn height(&self) -> u16 {
+        let rows = self.stats.interfaces(self.hide_virtual).count();
+        scroll::panel_height(rows).max(MIN_PANEL_LINES)
+    }
+
+    fn table(&self) -> Table<'static> {
+        let units = self.units;
+        let rows = self
+            .stats
+            .interfaces(self.hide_virtual)
+            .skip(self.view.scroll)
+            .map(|interface| {
+                let row = Row::new([
+                    interface.name.clone(),
+                    format::rate(interface.rx_rate, units),
+                    format::rate(interface.tx_rate, units),
+                    format::bytes(interface.rx_bytes, units),
+                    format::bytes(interface.tx_bytes, units),
+                    format!("{}/{}", interface.rx_packets, interface.tx_packets),
+                    interface.errors.to_string(),
+                ]);
+                if interface.errors > 0 {
+                    row.style(self.theme.error)
+                } else {
+                    row
+                }
+            });
+        let title = if self.hide_virtual {
+            "Network (physical)"
+        } else {
+            "Network"
+        };
+        Table::new(
+            rows,
+            [
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(15),
+                Constraint::Length(6),
+            ],
+        )
+        .header(
+            Row::new([
+                "Interface",
+                "RX/s",
+                "TX/s",
+                "RX",
+                "TX",
+                "Packets RX/TX",
+                "Errors",
+            ])
+            .style(self.theme.table_header),
+        )
+        .block(self.theme.panel_block(title, self.view.focused))
+    }
+}
+
+impl ScrollingPanel for NetworkPanel<'_> {
+    fn view_mut(&mut self) -> &mut PanelView {
+        &mut self.view
+    }
+}
+
+impl Widget for NetworkPanel<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [table_area, graph_area] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Percentage(40)]).areas(area);
+        StatefulWidget::render(self.table(), table_area, buf, &mut TableState::default());
+
+        let throughput = self.stats.throughput(self.hide_virtual);
+        let rx = throughput.rx.points(self.now, self.window);
+        let tx = throughput.tx.points(self.now, self.window);
+        // Scale to the busiest moment on screen, with a floor so an idle link isn't all noise.
+        let top = rx
+            .iter()
+            .chain(&tx)
+            .map(|&(_, rate)| rate)
+            .fold(1024.0, f64::max);
+        let window = self.window.as_secs_f64();
+        Chart::new(vec![
+            graphs::line(&tx, self.theme.transmit_line).name("TX"),
+            graphs::line(&rx, self.theme.receive_line).name("RX"),
+        ])
+        .block(
+            self.theme
+                .block(format!("Throughput (last {})", format::window(self.window))),
+        )
+        .x_axis(Axis::default().bounds([-window, 0.0]))
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, top])
+                .labels(["0".to_string(), format::rate(top, self.units)]),
+        )
+        .hidden_legend_constraints((Constraint::Percentage(50), Constraint::Percentage(50)))
+        .render(graph_area, buf);
+    }
+}
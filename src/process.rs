@@ -0,0 +1,527 @@
+//! The process table's data model.
+//!
+//! Rows are snapshotted from sysinfo once per sample and kept as raw numbers, so sorting and
+//! filtering work on real values. Turning them into text is left to the renderer.
+
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessStatus, System, UpdateKind, Users};
+
+use crate::format::{self, ByteUnits};
+
+#[derive(Debug, Clone)]
+pub struct ProcessRow {
+    pub pid: Pid,
+    pub parent: Option<Pid>,
+    pub name: String,
+    /// Percent of one core, so it can exceed 100 for multi-threaded processes.
+    pub cpu: f32,
+    /// Resident memory in bytes.
+    pub memory: u64,
+    /// Resident memory as a percentage of physical memory.
+    pub memory_percent: f32,
+    pub virtual_memory: u64,
+    /// Total CPU time used so far, in milliseconds.
+    pub cpu_time: u64,
+    /// When the process started, in seconds since the epoch.
+    pub start_time: u64,
+    /// Bytes per second read from and written to storage over the last sample.
+    pub disk_read: f64,
+    pub disk_write: f64,
+    /// The arguments joined with spaces. Empty unless the command line was refreshed.
+    pub command: String,
+    /// Empty unless the executable path was refreshed and could be read.
+    pub exe: String,
+    /// Empty unless the working directory was refreshed and could be read.
+    pub cwd: String,
+    /// The owner's user name, if the user list was refreshed and knows the UID.
+    pub user: Option<String>,
+    pub status: ProcessStatus,
+    /// Number of threads, where the platform reports them and they were refreshed.
+    pub threads: Option<usize>,
+    /// Kernel scheduling priority and nice value, when they were asked for. Only read on Linux.
+    pub priority: Option<i64>,
+    pub nice: Option<i64>,
+}
+
+/// What [`ProcessRow::new`] needs to know beyond the process itself.
+#[derive(Debug, Clone, Copy)]
+pub struct Context<'a> {
+    pub users: &'a Users,
+    /// The time since the previous sample, over which disk I/O is averaged. Without one there's
+    /// no rate to give yet.
+    pub elapsed: Option<Duration>,
+    pub total_memory: u64,
+    /// Whether to read priority and nice values, which sysinfo doesn't provide.
+    pub scheduling: bool,
+}
+
+impl ProcessRow {
+    pub fn new(process: &Process, context: &Context) -> Self {
+        let command = process
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let path = |path: Option<&std::path::Path>| {
+            path.map_or_else(String::new, |path| path.to_string_lossy().into_owned())
+        };
+        let disk = process.disk_usage();
+        let rate = |bytes: u64| {
+            context
+                .elapsed
+                .map_or(0.0, |elapsed| bytes as f64 / elapsed.as_secs_f64())
+        };
+        let memory_percent = if context.total_memory == 0 {
+            0.0
+        } else {
+            (process.memory() as f64 / context.total_memory as f64 * 100.0) as f32
+        };
+        let (priority, nice) = if context.scheduling {
+            scheduling(process.pid()).unzip()
+        } else {
+            (None, None)
+        };
+        Self {
+            pid: process.pid(),
+            parent: process.parent(),
+            name: process.name().to_string_lossy().into_owned(),
+            cpu: process.cpu_usage(),
+            memory: process.memory(),
+            memory_percent,
+            virtual_memory: process.virtual_memory(),
+            cpu_time: process.accumulated_cpu_time(),
+            start_time: process.start_time(),
+            disk_read: rate(disk.read_bytes),
+            disk_write: rate(disk.written_bytes),
+            command,
+            exe: path(process.exe()),
+            cwd: path(process.cwd()),
+            user: process
+                .user_id()
+                .and_then(|uid| context.users.get_user_by_id(uid))
+                .map(|user| user.name().to_string()),
+            status: process.status(),
+            threads: process.tasks().map(|tasks| tasks.len()),
+            priority,
+            nice,
+        }
+    }
+}
+
+/// Snapshots every process sysinfo currently knows about, in no particular order.
+///
+/// Threads are left out. On Linux sysinfo lists each one alongside its process, reporting the
+/// whole process's memory, so they'd crowd the table with copies of the biggest process and
+/// count its usage several times over in tree totals.
+pub fn collect(system: &System, context: &Context) -> Vec<ProcessRow> {
+    system
+        .processes()
+        .values()
+        .filter(|process| process.thread_kind().is_none())
+        .map(|process| ProcessRow::new(process, context))
+        .collect()
+}
+
+/// The priority and nice value from `/proc/<pid>/stat`, fields 18 and 19.
+#[cfg(target_os = "linux")]
+fn scheduling(pid: Pid) -> Option<(i64, i64)> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The command name in field 2 can contain spaces and parentheses, so count from the last
+    // closing parenthesis, after which the state is field 3.
+    let mut fields = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .skip(15);
+    let priority = fields.next()?.parse().ok()?;
+    let nice = fields.next()?.parse().ok()?;
+    Some((priority, nice))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn scheduling(_pid: Pid) -> Option<(i64, i64)> {
+    None
+}
+
+/// The one-letter state `ps` shows in its `S` column.
+fn state_letter(status: ProcessStatus) -> char {
+    match status {
+        ProcessStatus::Run => 'R',
+        ProcessStatus::Sleep => 'S',
+        ProcessStatus::Idle => 'I',
+        ProcessStatus::UninterruptibleDiskSleep => 'D',
+        ProcessStatus::Zombie => 'Z',
+        ProcessStatus::Stop => 'T',
+        ProcessStatus::Tracing => 't',
+        ProcessStatus::Dead => 'X',
+        ProcessStatus::Wakekill => 'K',
+        ProcessStatus::Waking => 'W',
+        ProcessStatus::Parked => 'P',
+        ProcessStatus::LockBlocked => 'L',
+        ProcessStatus::Unknown(_) => '?',
+    }
+}
+
+/// How many processes are in each state, as in top's "Tasks:" line. Threads aren't counted.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TaskCounts {
+    pub total: usize,
+    pub running: usize,
+    pub sleeping: usize,
+    pub stopped: usize,
+    pub zombie: usize,
+}
+
+impl TaskCounts {
+    pub fn count(rows: &[ProcessRow]) -> Self {
+        let mut counts = Self::default();
+        for row in rows {
+            counts.total += 1;
+            match row.status {
+                ProcessStatus::Run => counts.running += 1,
+                ProcessStatus::Sleep
+                | ProcessStatus::Idle
+                | ProcessStatus::UninterruptibleDiskSleep
+                | ProcessStatus::Parked
+                | ProcessStatus::Waking
+                | ProcessStatus::Wakekill
+                | ProcessStatus::LockBlocked => counts.sleeping += 1,
+                ProcessStatus::Stop | ProcessStatus::Tracing => counts.stopped += 1,
+                ProcessStatus::Zombie => counts.zombie += 1,
+                ProcessStatus::Dead | ProcessStatus::Unknown(_) => {}
+            }
+        }
+        counts
+    }
+}
+
+/// A column the process table can show. Which ones it does, and in what order, is up to the
+/// user; see [`DEFAULT_COLUMNS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Pid,
+    Ppid,
+    User,
+    State,
+    Priority,
+    Nice,
+    Threads,
+    Cpu,
+    MemoryPercent,
+    Virtual,
+    Memory,
+    DiskRead,
+    DiskWrite,
+    StartTime,
+    Time,
+    Name,
+    Command,
+    Exe,
+    Cwd,
+}
+
+/// The columns shown until the user picks their own.
+pub const DEFAULT_COLUMNS: [Column; 7] = [
+    Column::Pid,
+    Column::Name,
+    Column::Cpu,
+    Column::Memory,
+    Column::DiskRead,
+    Column::DiskWrite,
+    Column::Time,
+];
+
+impl Column {
+    /// Every column there is, in the order the setup screen offers them.
+    pub const ALL: [Self; 19] = [
+        Self::Pid,
+        Self::Ppid,
+        Self::User,
+        Self::State,
+        Self::Priority,
+        Self::Nice,
+        Self::Threads,
+        Self::Cpu,
+        Self::MemoryPercent,
+        Self::Virtual,
+        Self::Memory,
+        Self::DiskRead,
+        Self::DiskWrite,
+        Self::StartTime,
+        Self::Time,
+        Self::Name,
+        Self::Command,
+        Self::Exe,
+        Self::Cwd,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Pid => "PID",
+            Self::Ppid => "PPID",
+            Self::User => "USER",
+            Self::State => "S",
+            Self::Priority => "PRI",
+            Self::Nice => "NI",
+            Self::Threads => "THR",
+            Self::Cpu => "CPU%",
+            Self::MemoryPercent => "MEM%",
+            Self::Virtual => "VIRT",
+            Self::Memory => "RES",
+            Self::DiskRead => "DISK R/s",
+            Self::DiskWrite => "DISK W/s",
+            Self::StartTime => "START",
+            Self::Time => "TIME+",
+            Self::Name => "Name",
+            Self::Command => "Command",
+            Self::Exe => "Executable",
+            Self::Cwd => "Working directory",
+        }
+    }
+
+    /// What the column shows, for the setup screen.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Pid => "Process ID",
+            Self::Ppid => "Parent process ID",
+            Self::User => "Owner's user name",
+            Self::State => "Run state (R running, S sleeping, D disk wait, Z zombie, ...)",
+            Self::Priority => "Kernel scheduling priority",
+            Self::Nice => "Nice value",
+            Self::Threads => "Number of threads",
+            Self::Cpu => "CPU usage, in percent of one core",
+            Self::MemoryPercent => "Resident memory, in percent of physical memory",
+            Self::Virtual => "Virtual memory size",
+            Self::Memory => "Resident memory size",
+            Self::DiskRead => "Bytes read from storage per second",
+            Self::DiskWrite => "Bytes written to storage per second",
+            Self::StartTime => "When the process started",
+            Self::Time => "CPU time used so far",
+            Self::Name => "Process name",
+            Self::Command => "Full command line",
+            Self::Exe => "Path to the executable",
+            Self::Cwd => "Current working directory",
+        }
+    }
+
+    /// Whether the column takes whatever width is left over rather than just what its content
+    /// needs. These are the free-text columns that would otherwise crowd out the rest.
+    pub fn is_flexible(self) -> bool {
+        matches!(self, Self::Name | Self::Command | Self::Exe | Self::Cwd)
+    }
+
+    /// The widest a fixed column gets, however wide its content.
+    pub fn max_width(self) -> u16 {
+        match self {
+            Self::User => 12,
+            _ => 16,
+        }
+    }
+
+    /// How much the column is worth keeping when the table is too narrow for all of them. The
+    /// lowest are dropped first.
+    pub fn priority(self) -> u8 {
+        match self {
+            Self::Pid | Self::Name => 9,
+            Self::Cpu | Self::Memory => 8,
+            Self::User | Self::Command => 7,
+            Self::MemoryPercent | Self::Time => 6,
+            Self::State => 5,
+            Self::DiskRead | Self::DiskWrite | Self::Ppid => 4,
+            Self::Threads | Self::Virtual | Self::StartTime | Self::Exe => 3,
+            Self::Priority | Self::Nice => 2,
+            Self::Cwd => 1,
+        }
+    }
+
+    /// Adds what this column needs from sysinfo to `kind`, returning whether it also needs the
+    /// user list.
+    pub fn refresh_kind(self, kind: &mut ProcessRefreshKind) -> bool {
+        *kind = match self {
+            Self::User => kind.with_user(UpdateKind::OnlyIfNotSet),
+            Self::Threads => kind.with_tasks(),
+            Self::Cpu => kind.with_cpu(),
+            Self::MemoryPercent | Self::Virtual | Self::Memory => kind.with_memory(),
+            Self::DiskRead | Self::DiskWrite => kind.with_disk_usage(),
+            Self::Command => kind.with_cmd(UpdateKind::OnlyIfNotSet),
+            Self::Exe => kind.with_exe(UpdateKind::OnlyIfNotSet),
+            // A process can change directory at any time.
+            Self::Cwd => kind.with_cwd(UpdateKind::Always),
+            Self::Pid
+            | Self::Ppid
+            | Self::State
+            | Self::Priority
+            | Self::Nice
+            | Self::StartTime
+            | Self::Time
+            | Self::Name => *kind,
+        };
+        self == Self::User
+    }
+
+    pub fn cell(self, row: &ProcessRow, units: ByteUnits) -> String {
+        let optional =
+            |value: Option<i64>| value.map_or_else(|| "-".to_string(), |v| v.to_string());
+        match self {
+            Self::Pid => row.pid.to_string(),
+            Self::Ppid => row
+                .parent
+                .map_or_else(|| "-".to_string(), |pid| pid.to_string()),
+            Self::User => row.user.clone().unwrap_or_else(|| "-".to_string()),
+            Self::State => state_letter(row.status).to_string(),
+            Self::Priority => optional(row.priority),
+            Self::Nice => optional(row.nice),
+            Self::Threads => optional(row.threads.map(|threads| threads as i64)),
+            Self::Cpu => format!("{:.2}%", row.cpu),
+            Self::MemoryPercent => format!("{:.1}%", row.memory_percent),
+            Self::Virtual => format::bytes(row.virtual_memory, units),
+            Self::Memory => format::bytes(row.memory, units),
+            Self::DiskRead => format::rate(row.disk_read, units),
+            Self::DiskWrite => format::rate(row.disk_write, units),
+            Self::StartTime => format::start_time(row.start_time),
+            Self::Time => format::cpu_time(row.cpu_time),
+            Self::Name => row.name.clone(),
+            Self::Command => row.command.clone(),
+            Self::Exe => row.exe.clone(),
+            Self::Cwd => row.cwd.clone(),
+        }
+    }
+
+    /// Ascending order of `a` and `b` by this column.
+    fn compare(self, a: &ProcessRow, b: &ProcessRow) -> Ordering {
+        let text = |a: &str, b: &str| {
+            let a = a.chars().flat_map(char::to_lowercase);
+            let b = b.chars().flat_map(char::to_lowercase);
+            a.cmp(b)
+        };
+        match self {
+            Self::Pid => a.pid.cmp(&b.pid),
+            Self::Ppid => a.parent.cmp(&b.parent),
+            Self::User => a.user.cmp(&b.user),
+            Self::State => state_letter(a.status).cmp(&state_letter(b.status)),
+            Self::Priority => a.priority.cmp(&b.priority),
+            Self::Nice => a.nice.cmp(&b.nice),
+            Self::Threads => a.threads.cmp(&b.threads),
+            Self::Cpu => a.cpu.total_cmp(&b.cpu),
+            Self::MemoryPercent => a.memory_percent.total_cmp(&b.memory_percent),
+            Self::Virtual => a.virtual_memory.cmp(&b.virtual_memory),
+            Self::Memory => a.memory.cmp(&b.memory),
+            Self::DiskRead => a.disk_read.total_cmp(&b.disk_read),
+            Self::DiskWrite => a.disk_write.total_cmp(&b.disk_write),
+            Self::StartTime => a.start_time.cmp(&b.start_time),
+            Self::Time => a.cpu_time.cmp(&b.cpu_time),
+            Self::Name => text(&a.name, &b.name),
+            Self::Command => text(&a.command, &b.command),
+            Self::Exe => text(&a.exe, &b.exe),
+            Self::Cwd => text(&a.cwd, &b.cwd),
+        }
+    }
+
+    /// Numeric usage columns are most useful biggest-first; identifiers and text read naturally
+    /// A to Z.
+    fn descending_by_default(self) -> bool {
+        matches!(
+            self,
+            Self::Threads
+                | Self::Cpu
+                | Self::MemoryPercent
+                | Self::Virtual
+                | Self::Memory
+                | Self::DiskRead
+                | Self::DiskWrite
+                | Self::Time
+        )
+    }
+}
+
+/// Which column the process table is sorted by, and in which direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "SortFields")]
+pub struct Sort {
+    pub column: Column,
+    pub descending: bool,
+}
+
+/// [`Sort`] as written in the config file, where either field can be left out. The direction
+/// defaults to the column's natural one, so it can't be a plain serde default.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SortFields {
+    column: Option<Column>,
+    descending: Option<bool>,
+}
+
+impl From<SortFields> for Sort {
+    fn from(fields: SortFields) -> Self {
+        let sort = fields.column.map_or_else(Sort::default, Sort::by);
+        Self {
+            descending: fields.descending.unwrap_or(sort.descending),
+            ..sort
+        }
+    }
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Self::by(Column::Memory)
+    }
+}
+
+impl Sort {
+    /// Sorts by `column` in its natural direction.
+    pub fn by(column: Column) -> Self {
+        Self {
+            column,
+            descending: column.descending_by_default(),
+        }
+    }
+
+    /// Sorts by the column after the current one in `columns`, wrapping around. If the current
+    /// one isn't shown, starts from the first.
+    pub fn next(self, columns: &[Column]) -> Self {
+        let index = columns
+            .iter()
+            .position(|&column| column == self.column)
+            .map_or(0, |index| (index + 1) % columns.len());
+        columns.get(index).map_or(self, |&column| Self::by(column))
+    }
+
+    /// Sorts by the column before the current one in `columns`, wrapping around. If the current
+    /// one isn't shown, starts from the last.
+    pub fn previous(self, columns: &[Column]) -> Self {
+        let len = columns.len();
+        let index = columns
+            .iter()
+            .position(|&column| column == self.column)
+            .map_or(len.saturating_sub(1), |index| (index + len - 1) % len);
+        columns.get(index).map_or(self, |&column| Self::by(column))
+    }
+
+    pub fn reversed(self) -> Self {
+        Self {
+            descending: !self.descending,
+            ..self
+        }
+    }
+
+    /// The arrow shown next to the sorted column's title.
+    pub fn indicator(self) -> &'static str {
+        if self.descending { "▼" } else { "▲" }
+    }
+
+    /// Ties are broken by PID so rows with equal values don't shuffle between samples.
+    pub fn apply(self, rows: &mut [ProcessRow]) {
+        rows.sort_by(|a, b| {
+            let ordering = self.column.compare(a, b).then_with(|| a.pid.cmp(&b.pid));
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+}
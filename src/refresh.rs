@@ -0,0 +1,163 @@
+//! Deciding what to ask sysinfo for on each sample.
+//!
+//! `System::refresh_all` re-reads everything sysinfo knows about, including the full detail of
+//! every process, which gets expensive on hosts with thousands of them. Instead the app describes
+//! what its visible panels need as [`Needs`] and the [`RefreshPlanner`] refreshes just that, with
+//! slow-changing data picked up on a longer cadence.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use sysinfo::{
+    Components, CpuRefreshKind, DiskRefreshKind, Disks, Groups, MemoryRefreshKind, Networks, Pid,
+    ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind, Users,
+};
+
+/// How often slow-changing data (CPU frequencies, the user list, disk space, ...) is refreshed,
+/// regardless of the sample interval.
+const SLOW_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What the visible panels need from sysinfo on this sample.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Needs {
+    pub cpu: bool,
+    pub memory: bool,
+    pub swap: bool,
+    /// `None` skips the process list entirely; otherwise only the fields asked for are read.
+    pub processes: Option<ProcessRefreshKind>,
+    /// The user list, for turning process UIDs into names.
+    pub users: bool,
+    /// Interface byte and packet counters.
+    pub networks: bool,
+    /// Mounted filesystems and how full they are.
+    pub disks: bool,
+    /// Temperature sensors.
+    pub components: bool,
+    /// A process to read in full for the detail view.
+    pub detail: Option<Pid>,
+}
+
+/// Everything the app reads from sysinfo.
+pub struct Sources {
+    pub system: System,
+    pub users: Users,
+    /// Only read for the detail view, to name the process's group.
+    pub groups: Groups,
+    pub networks: Networks,
+    pub disks: Disks,
+    pub components: Components,
+}
+
+// Written out because `Groups` doesn't implement `Debug`, though the groups in it do.
+impl fmt::Debug for Sources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sources")
+            .field("system", &self.system)
+            .field("users", &self.users)
+            .field("groups", &self.groups.list())
+            .field("networks", &self.networks)
+            .field("disks", &self.disks)
+            .field("components", &self.components)
+            .finish()
+    }
+}
+
+impl Default for Sources {
+    fn default() -> Self {
+        Self {
+            system: System::new(),
+            users: Users::new(),
+            groups: Groups::new(),
+            networks: Networks::new(),
+            disks: Disks::new(),
+            components: Components::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RefreshPlanner {
+    last_slow: Option<Instant>,
+}
+
+impl RefreshPlanner {
+    /// Refreshes the parts of `sources` that `needs` asks for.
+    pub fn refresh(&mut self, sources: &mut Sources, needs: Needs) {
+        let system = &mut sources.system;
+        let now = Instant::now();
+        let slow = self
+            .last_slow
+            .is_none_or(|last| now.duration_since(last) >= SLOW_INTERVAL);
+
+        if needs.cpu {
+            let mut kind = CpuRefreshKind::nothing().with_cpu_usage();
+            if slow {
+                kind = kind.with_frequency();
+            }
+            system.refresh_cpu_specifics(kind);
+        }
+        if needs.memory || needs.swap {
+            let mut kind = MemoryRefreshKind::nothing();
+            if needs.memory {
+                kind = kind.with_ram();
+            }
+            if needs.swap {
+                kind = kind.with_swap();
+            }
+            system.refresh_memory_specifics(kind);
+        }
+        if let Some(kind) = needs.processes {
+            system.refresh_processes_specifics(ProcessesToUpdate::All, true, kind);
+        }
+        if let Some(pid) = needs.detail {
+            refresh_detail(sources, pid);
+        }
+        // Checked for emptiness too, so the names show up straight away the first time they're
+        // needed rather than at the next slow refresh.
+        if needs.users && (slow || sources.users.is_empty()) {
+            sources.users.refresh();
+        }
+        if needs.networks {
+            sources.networks.refresh(true);
+        }
+        // Free space rarely moves fast enough to be worth statting every mount each sample.
+        if needs.disks && (slow || sources.disks.list().is_empty()) {
+            sources
+                .disks
+                .refresh_specifics(true, DiskRefreshKind::nothing().with_storage());
+        }
+        if needs.components {
+            sources.components.refresh(true);
+        }
+
+        if slow {
+            self.last_slow = Some(now);
+        }
+    }
+}
+
+/// Reads everything the detail view shows about `pid`, and the user and group lists if they
+/// haven't been read yet.
+///
+/// CPU usage is left alone: it's computed from the time between refreshes, and a second refresh
+/// straight after the sample's would leave next to nothing to measure.
+pub fn refresh_detail(sources: &mut Sources, pid: Pid) {
+    let kind = ProcessRefreshKind::nothing()
+        .with_memory()
+        .with_tasks()
+        .with_cmd(UpdateKind::OnlyIfNotSet)
+        .with_environ(UpdateKind::OnlyIfNotSet)
+        .with_exe(UpdateKind::OnlyIfNotSet)
+        .with_root(UpdateKind::OnlyIfNotSet)
+        .with_user(UpdateKind::OnlyIfNotSet)
+        .with_cwd(UpdateKind::Always);
+    sources
+        .system
+        .refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), false, kind);
+    if sources.users.is_empty() {
+        sources.users.refresh();
+    }
+    if sources.groups.is_empty() {
+        sources.groups.refresh();
+    }
+}
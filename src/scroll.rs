@@ -0,0 +1,107 @@
+//! What the scrolling parts of the screen share: the table panels (network, disks, sensors and
+//! the process table) and the popups of text (help and process details).
+
+/// Lines a table panel spends on things other than rows: the top and bottom borders and the
+/// header row.
+pub const TABLE_CHROME_LINES: u16 = 3;
+
+/// The network, disks and sensors panels never grow past this many lines, borders included.
+pub const MAX_PANEL_LINES: u16 = 10;
+
+/// The height of a panel listing `rows` rows, within limits.
+pub fn panel_height(rows: usize) -> u16 {
+    let rows = rows.min(MAX_PANEL_LINES as usize) as u16;
+    (rows + TABLE_CHROME_LINES).min(MAX_PANEL_LINES)
+}
+
+/// Rows a table panel of `height` lines has room for.
+pub fn visible_rows(height: u16) -> usize {
+    height.saturating_sub(TABLE_CHROME_LINES) as usize
+}
+
+/// How far a panel is scrolled and whether it has focus.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PanelView {
+    /// Rows scrolled past at the top of the table.
+    pub scroll: usize,
+    pub focused: bool,
+}
+
+/// The builder methods of the panels that can have focus.
+pub trait ScrollingPanel: Sized {
+    fn view_mut(&mut self) -> &mut PanelView;
+
+    fn scroll(mut self, scroll: usize) -> Self {
+        self.view_mut().scroll = scroll;
+        self
+    }
+
+    fn focused(mut self, focused: bool) -> Self {
+        self.view_mut().focused = focused;
+        self
+    }
+}
+
+/// A popup of text scrolled a line at a time.
+pub trait ScrollingText {
+    fn scroll_down(&mut self, lines: u16);
+
+    fn scroll_up(&mut self, lines: u16);
+
+    /// Scrolls by `lines`, down if positive.
+    fn scroll_by(&mut self, lines: isize) {
+        let amount = lines.unsigned_abs().min(u16::MAX as usize) as u16;
+        if lines > 0 {
+            self.scroll_down(amount);
+        } else {
+            self.scroll_up(amount);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Text {
+        scroll: u16,
+    }
+
+    impl ScrollingText for Text {
+        fn scroll_down(&mut self, lines: u16) {
+            self.scroll = self.scroll.saturating_add(lines).min(20);
+        }
+
+        fn scroll_up(&mut self, lines: u16) {
+            self.scroll = self.scroll.saturating_sub(lines);
+        }
+    }
+
+    #[test]
+    fn panel_height_adds_chrome_within_limits() {
+        assert_eq!(panel_height(0), 3);
+        assert_eq!(panel_height(4), 7);
+        assert_eq!(panel_height(7), MAX_PANEL_LINES);
+        assert_eq!(panel_height(usize::MAX), MAX_PANEL_LINES);
+    }
+
+    #[test]
+    fn visible_rows_leaves_out_chrome() {
+        assert_eq!(visible_rows(10), 7);
+        assert_eq!(visible_rows(2), 0);
+    }
+
+    #[test]
+    fn scroll_by_goes_both_ways() {
+        let mut text = Text::default();
+        text.scroll_by(5);
+        assert_eq!(text.scroll, 5);
+        text.scroll_by(-2);
+        assert_eq!(text.scroll, 3);
+        text.scroll_by(isize::MAX);
+        assert_eq!(text.scroll, 20);
+        text.scroll_by(isize::MIN);
+        assert_eq!(text.scroll, 0);
+    }
+}
@@ -0,0 +1,314 @@
+//! Searching and filtering the process table.
+//!
+//! A query is matched against each process's PID, name, command line and user. In filter mode
+//! non-matching processes are hidden; in search mode everything stays visible and `n`/`N` jump
+//! between matches.
+
+use regex::Regex;
+
+use crate::process::ProcessRow;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Hide processes that don't match.
+    #[default]
+    Filter,
+    /// Keep every process and jump between the ones that match.
+    Search,
+}
+
+impl SearchMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Filter => Self::Search,
+            Self::Search => Self::Filter,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Filter => "filter",
+            Self::Search => "search",
+        }
+    }
+}
+
+/// How the query text is compared against a process.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Matching {
+    #[default]
+    IgnoreCase,
+    CaseSensitive,
+    Regex,
+}
+
+impl Matching {
+    pub fn next(self) -> Self {
+        match self {
+            Self::IgnoreCase => Self::CaseSensitive,
+            Self::CaseSensitive => Self::Regex,
+            Self::Regex => Self::IgnoreCase,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::IgnoreCase => "ignore case",
+            Self::CaseSensitive => "match case",
+            Self::Regex => "regex",
+        }
+    }
+}
+
+/// A compiled query, ready to test rows against.
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// The lowercased needle.
+    IgnoreCase(String),
+    CaseSensitive(String),
+    Regex(Regex),
+    /// A regex that failed to compile; matches nothing.
+    Invalid,
+}
+
+#[derive(Debug, Default)]
+pub struct Search {
+    text: String,
+    mode: SearchMode,
+    matching: Matching,
+    matcher: Option<Matcher>,
+    /// Whether the search bar has keyboard focus.
+    editing: bool,
+}
+
+impl Search {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn mode(&self) -> SearchMode {
+        self.mode
+    }
+
+    pub fn matching(&self) -> Matching {
+        self.matching
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    /// Whether there's a query in effect.
+    pub fn is_active(&self) -> bool {
+        self.matcher.is_some()
+    }
+
+    /// Whether the query is a regex that doesn't compile.
+    pub fn is_invalid(&self) -> bool {
+        matches!(self.matcher, Some(Matcher::Invalid))
+    }
+
+    /// Whether rows should be hidden when they don't match.
+    pub fn is_filtering(&self) -> bool {
+        self.is_active() && self.mode == SearchMode::Filter
+    }
+
+    pub fn open(&mut self) {
+        self.editing = true;
+    }
+
+    /// Closes the search bar, keeping the query in effect.
+    pub fn accept(&mut self) {
+        self.editing = false;
+    }
+
+    /// Closes the search bar and drops the query.
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.matcher = None;
+        self.editing = false;
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.text.push(c);
+        self.compile();
+    }
+
+    pub fn pop(&mut self) {
+        self.text.pop();
+        self.compile();
+    }
+
+    pub fn clear_text(&mut self) {
+        self.text.clear();
+        self.compile();
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = self.mode.toggled();
+    }
+
+    pub fn cycle_matching(&mut self) {
+        self.matching = self.matching.next();
+        self.compile();
+    }
+
+    pub fn matches(&self, row: &ProcessRow) -> bool {
+        let Some(matcher) = &self.matcher else {
+            return true;
+        };
+        let fields = [
+            row.name.as_str(),
+            row.command.as_str(),
+            row.user.as_deref().unwrap_or_default(),
+        ];
+        let pid = row.pid.to_string();
+        match matcher {
+            Matcher::IgnoreCase(needle) => {
+                pid.contains(needle.as_str())
+                    || fields
+                        .iter()
+                        .any(|field| field.to_lowercase().contains(needle.as_str()))
+            }
+            Matcher::CaseSensitive(needle) => {
+                pid.contains(needle.as_str())
+                    || fields.iter().any(|field| field.contains(needle.as_str()))
+            }
+            Matcher::Regex(regex) => {
+                regex.is_match(&pid) || fields.iter().any(|field| regex.is_match(field))
+            }
+            Matcher::Invalid => false,
+        }
+    }
+
+    fn compile(&mut self) {
+        if self.text.is_empty() {
+            self.matcher = None;
+            return;
+        }
+        self.matcher = Some(match self.matching {
+            Matching::IgnoreCase => Matcher::IgnoreCase(self.text.to_lowercase()),
+            Matching::CaseSensitive => Matcher::CaseSensitive(self.text.clone()),
+            Matching::Regex => Regex::new(&self.text).map_or(Matcher::Invalid, Matcher::Regex),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sysinfo::{Pid, ProcessStatus};
+
+    use super::*;
+
+    fn row(pid: u32, name: &str, command: &str, user: Option<&str>) -> ProcessRow {
+        ProcessRow {
+            pid: Pid::from_u32(pid),
+            parent: None,
+            name: name.to_string(),
+            cpu: 0.0,
+            memory: 0,
+            memory_percent: 0.0,
+            virtual_memory: 0,
+            cpu_time: 0,
+            start_time: 0,
+            disk_read: 0.0,
+            disk_write: 0.0,
+            command: command.to_string(),
+            exe: String::new(),
+            cwd: String::new(),
+            user: user.map(str::to_string),
+            status: ProcessStatus::Run,
+            threads: None,
+            priority: None,
+            nice: None,
+        }
+    }
+
+    fn search(matching: Matching, text: &str) -> Search {
+        let mut search = Search {
+            matching,
+            ..Search::default()
+        };
+        for c in text.chars() {
+            search.push(c);
+        }
+        search
+    }
+
+    fn nginx() -> ProcessRow {
+        row(
+            4242,
+            "nginx",
+            "/usr/sbin/nginx -g daemon off;",
+            Some("www-data"),
+        )
+    }
+
+    #[test]
+    fn no_query_matches_everything() {
+        let search = Search::default();
+        assert!(!search.is_active());
+        assert!(search.matches(&nginx()));
+    }
+
+    #[test]
+    fn matches_pid_name_command_and_user() {
+        let nginx = nginx();
+        for text in ["424", "ngin", "daemon off", "www"] {
+            assert!(search(Matching::IgnoreCase, text).matches(&nginx), "{text}");
+        }
+        assert!(!search(Matching::IgnoreCase, "apache").matches(&nginx));
+        // The user may not have been read.
+        assert!(!search(Matching::IgnoreCase, "www").matches(&row(1, "a", "", None)));
+    }
+
+    #[test]
+    fn ignore_case_folds_both_sides() {
+        let row = row(1, "Xorg", "/usr/bin/Xorg :0", Some("root"));
+        assert!(search(Matching::IgnoreCase, "xorg").matches(&row));
+        assert!(search(Matching::IgnoreCase, "XORG").matches(&row));
+        assert!(search(Matching::IgnoreCase, "ROOT").matches(&row));
+    }
+
+    #[test]
+    fn case_sensitive_is_exact() {
+        let row = row(1, "Xorg", "/usr/bin/Xorg :0", Some("root"));
+        assert!(search(Matching::CaseSensitive, "Xorg").matches(&row));
+        assert!(!search(Matching::CaseSensitive, "xorg").matches(&row));
+        assert!(search(Matching::CaseSensitive, "1").matches(&row));
+    }
+
+    #[test]
+    fn regex_matches_any_field() {
+        let row = nginx();
+        assert!(search(Matching::Regex, "^ngin.$").matches(&row));
+        assert!(search(Matching::Regex, r"^\d{4}$").matches(&row));
+        assert!(search(Matching::Regex, "www-(data|web)").matches(&row));
+        assert!(!search(Matching::Regex, "^daemon").matches(&row));
+    }
+
+    #[test]
+    fn invalid_regex_matches_nothing() {
+        let search = search(Matching::Regex, "ngin(x");
+        assert!(search.is_active());
+        assert!(search.is_invalid());
+        assert!(!search.matches(&nginx()));
+    }
+
+    #[test]
+    fn matching_can_change_after_typing() {
+        let mut search = search(Matching::IgnoreCase, "NGINX");
+        assert!(search.matches(&nginx()));
+        search.cycle_matching();
+        assert_eq!(search.matching(), Matching::CaseSensitive);
+        assert!(!search.matches(&nginx()));
+    }
+
+    #[test]
+    fn emptying_the_query_clears_it() {
+        let mut search = search(Matching::IgnoreCase, "x");
+        search.pop();
+        assert!(!search.is_active());
+        assert!(search.matches(&nginx()));
+    }
+}
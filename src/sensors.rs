@@ -0,0 +1,126 @@
+//! The sensors panel, and finding the hottest CPU sensor for the header.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::Style,
+    widgets::{Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use sysinfo::{Component, Components};
+
+use crate::format;
+use crate::scroll::{self, PanelView, ScrollingPanel};
+use crate::theme::Theme;
+
+/// What we measure against when a sensor doesn't report a critical temperature.
+const DEFAULT_CRITICAL: f32 = 100.0;
+
+/// Substrings of labels that belong to CPU sensors on the common drivers (coretemp, k10temp,
+/// and the SoC sensors of ARM boards).
+const CPU_LABELS: [&str; 5] = ["cpu", "core", "package", "tctl", "tdie"];
+
+/// The style for a temperature: the theme's low with plenty of headroom, medium as it gets
+/// within a quarter of critical, high within a tenth.
+pub fn temperature_style(celsius: f32, critical: Option<f32>, theme: &Theme) -> Style {
+    let critical = critical.filter(|&critical| critical > 0.0);
+    let fraction = celsius / critical.unwrap_or(DEFAULT_CRITICAL);
+    if fraction >= 0.9 {
+        theme.high
+    } else if fraction >= 0.75 {
+        theme.medium
+    } else {
+        theme.low
+    }
+}
+
+/// The CPU sensor currently reading highest, if any look like CPU sensors.
+pub fn hottest_cpu(components: &Components) -> Option<&Component> {
+    components
+        .list()
+        .iter()
+        .filter(|component| {
+            let label = component.label().to_lowercase();
+            CPU_LABELS.iter().any(|cpu| label.contains(cpu))
+        })
+        .filter(|component| component.temperature().is_some_and(f32::is_finite))
+        .max_by(|a, b| {
+            let (a, b) = (a.temperature(), b.temperature());
+            a.unwrap_or(0.0).total_cmp(&b.unwrap_or(0.0))
+        })
+}
+
+#[derive(Debug)]
+pub struct SensorsPanel<'a> {
+    components: &'a Components,
+    theme: &'a Theme,
+    view: PanelView,
+}
+
+impl<'a> SensorsPanel<'a> {
+    pub fn new(components: &'a Components, theme: &'a Theme) -> Self {
+        Self {
+            components,
+            theme,
+            view: PanelView::default(),
+        }
+    }
+
+    /// The height needed to list every sensor, within limits. With none, the line for the
+    /// header says so instead.
+    pub fn height(&self) -> u16 {
+        scroll::panel_height(self.components.list().len())
+    }
+}
+
+impl ScrollingPanel for SensorsPanel<'_> {
+    fn view_mut(&mut self) -> &mut PanelView {
+        &mut self.view
+    }
+}
+
+impl Widget for SensorsPanel<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let theme = self.theme;
+        let block = theme.panel_block("Sensors", self.view.focused);
+        // VMs and containers usually expose no sensors at all.
+        if self.components.list().is_empty() {
+            Paragraph::new("No sensors")
+                .style(theme.dim)
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+
+        let rows = self
+            .components
+            .list()
+            .iter()
+            .skip(self.view.scroll)
+            .map(|component| {
+                let temperature = component.temperature();
+                let critical = component.critical();
+                let style = temperature.map_or(theme.dim, |celsius| {
+                    temperature_style(celsius, critical, theme)
+                });
+                Row::new([
+                    component.label().to_string(),
+                    format::temperature(temperature),
+                    format::temperature(component.max()),
+                    format::temperature(critical),
+                ])
+                .style(style)
+            });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(20),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(8),
+            ],
+        )
+        .header(Row::new(["Sensor", "Current", "Max", "Critical"]).style(theme.table_header))
+        .block(block);
+        StatefulWidget::render(table, area, buf, &mut TableState::default());
+    }
+}
@@ -0,0 +1,266 @@
+//! Selection, scrolling, filtering and tree state for the process table.
+//!
+//! The selection is tracked by PID rather than by row index: rows are re-collected and re-sorted
+//! on every sample, and the highlighted process should stay highlighted as it moves around.
+
+use std::collections::HashSet;
+
+use ratatui::widgets::{ScrollbarState, TableState};
+use sysinfo::Pid;
+
+use crate::process::{ProcessRow, Sort};
+use crate::search::Search;
+use crate::tree::{self, TreeRow};
+
+#[derive(Debug, Default)]
+pub struct ProcessTable {
+    /// Every process from the last sample, sorted.
+    all: Vec<ProcessRow>,
+    /// The rows on screen, in display order. The flat view is a forest of roots without guides.
+    view: Vec<TreeRow>,
+    sort: Sort,
+    search: Search,
+    tree: bool,
+    collapsed: HashSet<Pid>,
+    state: TableState,
+    selected_pid: Option<Pid>,
+    /// Number of rows visible at the last render, used as the page size.
+    page_height: usize,
+}
+
+impl ProcessTable {
+    /// The rows on screen, in display order, each with the tree guide to draw before its name.
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = (&ProcessRow, &str)> {
+        self.view
+            .iter()
+            .map(|row| (self.row(row), row.prefix.as_str()))
+    }
+
+    fn row<'a>(&'a self, view_row: &'a TreeRow) -> &'a ProcessRow {
+        view_row
+            .totals
+            .as_ref()
+            .unwrap_or(&self.all[view_row.index])
+    }
+
+    pub fn is_tree(&self) -> bool {
+        self.tree
+    }
+
+    pub fn toggle_tree(&mut self) {
+        self.tree = !self.tree;
+        self.rebuild_view();
+    }
+
+    pub fn sort(&self) -> Sort {
+        self.sort
+    }
+
+    pub fn search(&self) -> &Search {
+        &self.search
+    }
+
+    /// Call [`Self::apply_search`] after changing the query to update the view.
+    pub fn search_mut(&mut self) -> &mut Search {
+        &mut self.search
+    }
+
+    pub fn state_mut(&mut self) -> &mut TableState {
+        &mut self.state
+    }
+
+    pub fn scrollbar_state(&self) -> ScrollbarState {
+        ScrollbarState::new(self.view.len()).position(self.state.selected().unwrap_or(0))
+    }
+
+    pub fn selected(&self) -> Option<&ProcessRow> {
+        self.state
+            .selected()
+            .and_then(|index| self.view.get(index))
+            .map(|row| &self.all[row.index])
+    }
+
+    /// How many processes match the current query.
+    pub fn match_count(&self) -> usize {
+        if self.search.is_filtering() {
+            self.view.len()
+        } else {
+            self.all
+                .iter()
+                .filter(|row| self.search.matches(row))
+                .count()
+        }
+    }
+
+    pub fn page_height(&self) -> usize {
+        self.page_height
+    }
+
+    pub fn set_page_height(&mut self, height: usize) {
+        self.page_height = height.max(1);
+    }
+
+    /// Replaces the rows with a fresh sample, keeping the selected process selected.
+    pub fn update(&mut self, rows: Vec<ProcessRow>) {
+        self.all = rows;
+        self.sort.apply(&mut self.all);
+        self.rebuild_view();
+    }
+
+    pub fn set_sort(&mut self, sort: Sort) {
+        self.sort = sort;
+        self.sort.apply(&mut self.all);
+        self.rebuild_view();
+    }
+
+    /// Rebuilds the view after the query changed.
+    pub fn apply_search(&mut self) {
+        self.rebuild_view();
+    }
+
+    /// Selects the next (or previous) row matching the query after the current one, wrapping
+    /// around. Returns whether there was a match to jump to.
+    pub fn select_match(&mut self, forward: bool) -> bool {
+        let len = self.view.len();
+        if len == 0 || !self.search.is_active() {
+            return false;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        let found = (1..=len)
+            .map(|step| {
+                if forward {
+                    (current + step) % len
+                } else {
+                    (current + len - step) % len
+                }
+            })
+            .find(|&index| self.search.matches(&self.all[self.view[index].index]));
+        if let Some(index) = found {
+            self.select(index);
+        }
+        found.is_some()
+    }
+
+    pub fn select_next(&mut self) {
+        self.select_relative(1);
+    }
+
+    pub fn select_previous(&mut self) {
+        self.select_relative(-1);
+    }
+
+    pub fn page_down(&mut self) {
+        self.select_relative(self.page_height as isize);
+    }
+
+    pub fn page_up(&mut self) {
+        self.select_relative(-(self.page_height as isize));
+    }
+
+    pub fn select_first(&mut self) {
+        self.select(0);
+    }
+
+    pub fn select_last(&mut self) {
+        self.select(self.view.len().saturating_sub(1));
+    }
+
+    /// Moves the selection `delta` rows down, or up if negative.
+    pub fn select_relative(&mut self, delta: isize) {
+        let current = self.state.selected().unwrap_or(0);
+        self.select(current.saturating_add_signed(delta));
+    }
+
+    /// The index of the row drawn `line` lines below the first visible one at the last render,
+    /// if there is one there.
+    pub fn row_at(&self, line: usize) -> Option<usize> {
+        let index = self.state.offset() + line;
+        (index < self.view.len()).then_some(index)
+    }
+
+    /// Selects the row at `index`, clamped to the table.
+    pub fn select(&mut self, index: usize) {
+        if self.view.is_empty() {
+            self.state.select(None);
+            self.selected_pid = None;
+            return;
+        }
+        let index = index.min(self.view.len() - 1);
+        self.state.select(Some(index));
+        self.selected_pid = Some(self.all[self.view[index].index].pid);
+    }
+
+    /// Collapses the selected subtree, or if it's already collapsed or has no children, moves
+    /// to its parent.
+    pub fn collapse(&mut self) {
+        let Some((pid, parent, expanded)) = self.selected_node() else {
+            return;
+        };
+        if expanded {
+            self.collapsed.insert(pid);
+            self.rebuild_view();
+        } else if let Some(position) = parent.and_then(|parent| self.position_of(parent)) {
+            self.select(position);
+        }
+    }
+
+    pub fn expand(&mut self) {
+        if let Some((pid, _, _)) = self.selected_node()
+            && self.collapsed.remove(&pid)
+        {
+            self.rebuild_view();
+        }
+    }
+
+    /// The selected process's PID and parent, and whether it has expanded children. `None`
+    /// outside the tree view.
+    fn selected_node(&self) -> Option<(Pid, Option<Pid>, bool)> {
+        if !self.tree {
+            return None;
+        }
+        let row = &self.view[self.state.selected()?];
+        let process = &self.all[row.index];
+        let expanded = row.has_children && row.totals.is_none();
+        Some((process.pid, process.parent, expanded))
+    }
+
+    fn position_of(&self, pid: Pid) -> Option<usize> {
+        self.view
+            .iter()
+            .position(|row| self.all[row.index].pid == pid)
+    }
+
+    fn rebuild_view(&mut self) {
+        if self.tree {
+            // Forget collapsed PIDs that have exited so a reused PID doesn't start out collapsed.
+            let present: HashSet<_> = self.all.iter().map(|row| row.pid).collect();
+            self.collapsed.retain(|pid| present.contains(pid));
+        }
+        let filtering = self.search.is_filtering();
+        let keep = |row: &ProcessRow| !filtering || self.search.matches(row);
+        self.view = if self.tree {
+            tree::flatten(&self.all, &self.collapsed, keep)
+        } else {
+            (0..self.all.len())
+                .filter(|&index| keep(&self.all[index]))
+                .map(|index| TreeRow {
+                    index,
+                    prefix: String::new(),
+                    has_children: false,
+                    totals: None,
+                })
+                .collect()
+        };
+        self.reselect();
+    }
+
+    /// Finds the selected PID again after the rows changed. If that process has exited, the
+    /// selection stays at the same position so it lands on a neighbour rather than jumping away.
+    fn reselect(&mut self) {
+        let position = self.selected_pid.and_then(|pid| self.position_of(pid));
+        match position {
+            Some(index) => self.state.select(Some(index)),
+            None => self.select(self.state.selected().unwrap_or(0)),
+        }
+    }
+}
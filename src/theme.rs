@@ -0,0 +1,508 @@
+//! Colors and text styles for everything on screen.
+//!
+//! A theme is one of the built-in ones, or a table in the config file that starts from one of
+//! them and restyles some elements. Whichever it is gets cut down to what the terminal can show:
+//! 256 or 16 colors when there's no truecolor, and none at all when `NO_COLOR` is set.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use color_eyre::{Result, Section, eyre::eyre};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders},
+};
+
+/// The built-in themes, by name.
+pub const BUILT_IN: [&str; 5] = [
+    "default",
+    "light",
+    "high-contrast",
+    "monochrome",
+    "solarized",
+];
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Panel and popup borders.
+    pub border: Style,
+    /// The border of the panel that has focus.
+    pub focus: Style,
+    /// Panel and popup titles.
+    pub title: Style,
+    /// Labels in front of values, as in the header and on the detail screen.
+    pub label: Style,
+    /// Secondary text, and the empty part of gauges.
+    pub dim: Style,
+    /// Column titles in tables.
+    pub table_header: Style,
+    /// Section headings in longer text.
+    pub heading: Style,
+    /// The selected row of a table or list.
+    pub selected: Style,
+    /// Processes matching the search.
+    pub search_match: Style,
+    /// Keys in the help and the shortcut bar.
+    pub key: Style,
+    /// Gauges and readings with plenty of headroom.
+    pub low: Style,
+    /// Gauges and readings getting busy or hot.
+    pub medium: Style,
+    /// Gauges and readings near their limit.
+    pub high: Style,
+    pub cpu_line: Style,
+    /// The per-core lines behind the total CPU line.
+    pub core_line: Style,
+    pub memory_line: Style,
+    pub swap_line: Style,
+    pub receive_line: Style,
+    pub transmit_line: Style,
+    /// Failures and things that need attention, such as zombies and interface errors.
+    pub error: Style,
+    /// Messages saying something worked.
+    pub info: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let fg = |color| Style::default().fg(color);
+        Self {
+            border: Style::default(),
+            focus: fg(Color::Cyan),
+            title: Style::default(),
+            label: fg(Color::Green),
+            dim: fg(Color::DarkGray),
+            table_header: Style::default().add_modifier(Modifier::BOLD),
+            heading: Style::default().add_modifier(Modifier::BOLD),
+            selected: Style::default().add_modifier(Modifier::REVERSED),
+            search_match: fg(Color::Yellow),
+            key: fg(Color::Cyan),
+            low: fg(Color::Green),
+            medium: fg(Color::Yellow),
+            high: fg(Color::Red),
+            cpu_line: fg(Color::Cyan),
+            core_line: fg(Color::DarkGray),
+            memory_line: fg(Color::Green),
+            swap_line: fg(Color::Magenta),
+            receive_line: fg(Color::Cyan),
+            transmit_line: fg(Color::Magenta),
+            error: fg(Color::Red),
+            info: fg(Color::Green),
+        }
+    }
+}
+
+impl Theme {
+    /// A built-in theme by name.
+    pub fn built_in(name: &str) -> Option<Self> {
+        let fg = |color| Style::default().fg(color);
+        let bold = |color| Style::default().fg(color).add_modifier(Modifier::BOLD);
+        let theme = match name {
+            "default" => Self::default(),
+            // For dark text on a light background, where yellow and the grays wash out.
+            "light" => Self {
+                border: fg(Color::Gray),
+                focus: fg(Color::Blue),
+                title: Style::default().add_modifier(Modifier::BOLD),
+                label: fg(Color::Blue),
+                dim: fg(Color::Gray),
+                search_match: fg(Color::Magenta),
+                key: fg(Color::Blue),
+                medium: fg(Color::Rgb(0xaf, 0x87, 0x00)),
+                cpu_line: fg(Color::Blue),
+                core_line: fg(Color::Gray),
+                receive_line: fg(Color::Blue),
+                ..Self::default()
+            },
+            "high-contrast" => Self {
+                border: fg(Color::White),
+                focus: bold(Color::LightCyan),
+                title: bold(Color::White),
+                label: bold(Color::LightYellow),
+                dim: fg(Color::Gray),
+                table_header: bold(Color::White).add_modifier(Modifier::UNDERLINED),
+                heading: bold(Color::White),
+                selected: bold(Color::Black).bg(Color::LightYellow),
+                search_match: bold(Color::LightMagenta),
+                key: bold(Color::LightCyan),
+                low: fg(Color::LightGreen),
+                medium: fg(Color::LightYellow),
+                high: bold(Color::LightRed),
+                cpu_line: fg(Color::LightCyan),
+                core_line: fg(Color::Gray),
+                memory_line: fg(Color::LightGreen),
+                swap_line: fg(Color::LightMagenta),
+                receive_line: fg(Color::LightCyan),
+                transmit_line: fg(Color::LightMagenta),
+                error: bold(Color::LightRed),
+                info: bold(Color::LightGreen),
+            },
+            // Everything told apart by weight and decoration alone.
+            "monochrome" => {
+                let plain = Style::default();
+                let with = |modifier| Style::default().add_modifier(modifier);
+                Self {
+                    border: plain,
+                    focus: with(Modifier::BOLD),
+                    title: with(Modifier::BOLD),
+                    label: with(Modifier::BOLD),
+                    dim: with(Modifier::DIM),
+                    table_header: with(Modifier::BOLD | Modifier::UNDERLINED),
+                    heading: with(Modifier::BOLD | Modifier::UNDERLINED),
+                    selected: with(Modifier::REVERSED),
+                    search_match: with(Modifier::UNDERLINED),
+                    key: with(Modifier::REVERSED),
+                    low: plain,
+                    medium: with(Modifier::BOLD),
+                    high: with(Modifier::BOLD | Modifier::UNDERLINED),
+                    cpu_line: plain,
+                    core_line: with(Modifier::DIM),
+                    memory_line: plain,
+                    swap_line: with(Modifier::DIM),
+                    receive_line: plain,
+                    transmit_line: with(Modifier::DIM),
+                    error: with(Modifier::BOLD),
+                    info: plain,
+                }
+            }
+            "solarized" => {
+                let base01 = Color::Rgb(0x58, 0x6e, 0x75);
+                let base02 = Color::Rgb(0x07, 0x36, 0x42);
+                let base1 = Color::Rgb(0x93, 0xa1, 0xa1);
+                let yellow = Color::Rgb(0xb5, 0x89, 0x00);
+                let red = Color::Rgb(0xdc, 0x32, 0x2f);
+                let magenta = Color::Rgb(0xd3, 0x36, 0x82);
+                let violet = Color::Rgb(0x6c, 0x71, 0xc4);
+                let blue = Color::Rgb(0x26, 0x8b, 0xd2);
+                let cyan = Color::Rgb(0x2a, 0xa1, 0x98);
+                let green = Color::Rgb(0x85, 0x99, 0x00);
+                Self {
+                    border: fg(base01),
+                    focus: fg(blue),
+                    title: bold(base1),
+                    label: fg(blue),
+                    dim: fg(base01),
+                    table_header: bold(base1),
+                    heading: bold(base1),
+                    selected: bold(base1).bg(base02),
+                    search_match: fg(yellow),
+                    key: fg(cyan),
+                    low: fg(green),
+                    medium: fg(yellow),
+                    high: fg(red),
+                    cpu_line: fg(blue),
+                    core_line: fg(base01),
+                    memory_line: fg(green),
+                    swap_line: fg(magenta),
+                    receive_line: fg(cyan),
+                    transmit_line: fg(violet),
+                    error: fg(red),
+                    info: fg(green),
+                }
+            }
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /// The theme called `name`: one of `themes` from the config file, or a built-in one.
+    ///
+    /// A config theme maps element names to styles, and may name a `base` theme to start from
+    /// (the default otherwise).
+    pub fn load(name: &str, themes: &BTreeMap<String, BTreeMap<String, String>>) -> Result<Self> {
+        let Some(elements) = themes.get(name) else {
+            return Self::built_in(name)
+                .ok_or_else(|| eyre!("there is no theme called `{name}`"))
+                .with_suggestion(|| {
+                    let mut names: Vec<&str> = BUILT_IN.to_vec();
+                    names.extend(themes.keys().map(String::as_str));
+                    format!("use one of {}", names.join(", "))
+                });
+        };
+        let base = elements.get("base").map_or("default", String::as_str);
+        let mut theme = Self::built_in(base)
+            .ok_or_else(|| {
+                eyre!("theme `{name}` is based on `{base}`, which isn't a built-in theme")
+            })
+            .with_suggestion(|| format!("use one of {}", BUILT_IN.join(", ")))?;
+
+        let mut problems = Vec::new();
+        for (element, value) in elements {
+            if element == "base" {
+                continue;
+            }
+            let Some(style) = theme.element_mut(element) else {
+                problems.push(format!("`{element}` is not something a theme can style"));
+                continue;
+            };
+            match parse_style(value) {
+                Ok(parsed) => *style = parsed,
+                Err(problem) => problems.push(format!("{element}: {problem}")),
+            }
+        }
+        if problems.is_empty() {
+            Ok(theme)
+        } else {
+            Err(eyre!(
+                "theme `{name}` has problems:\n  {}",
+                problems.join("\n  ")
+            ))
+            .suggestion("fix the theme's table in the config file")
+        }
+    }
+
+    fn element_mut(&mut self, name: &str) -> Option<&mut Style> {
+        let style = match name {
+            "border" => &mut self.border,
+            "focus" => &mut self.focus,
+            "title" => &mut self.title,
+            "label" => &mut self.label,
+            "dim" => &mut self.dim,
+            "table_header" => &mut self.table_header,
+            "heading" => &mut self.heading,
+            "selected" => &mut self.selected,
+            "search_match" => &mut self.search_match,
+            "key" => &mut self.key,
+            "low" => &mut self.low,
+            "medium" => &mut self.medium,
+            "high" => &mut self.high,
+            "cpu_line" => &mut self.cpu_line,
+            "core_line" => &mut self.core_line,
+            "memory_line" => &mut self.memory_line,
+            "swap_line" => &mut self.swap_line,
+            "receive_line" => &mut self.receive_line,
+            "transmit_line" => &mut self.transmit_line,
+            "error" => &mut self.error,
+            "info" => &mut self.info,
+            _ => return None,
+        };
+        Some(style)
+    }
+
+    /// Applies `f` to every style in the theme.
+    fn map(mut self, f: impl Fn(Style) -> Style) -> Self {
+        for style in [
+            &mut self.border,
+            &mut self.focus,
+            &mut self.title,
+            &mut self.label,
+            &mut self.dim,
+            &mut self.table_header,
+            &mut self.heading,
+            &mut self.selected,
+            &mut self.search_match,
+            &mut self.key,
+            &mut self.low,
+            &mut self.medium,
+            &mut self.high,
+            &mut self.cpu_line,
+            &mut self.core_line,
+            &mut self.memory_line,
+            &mut self.swap_line,
+            &mut self.receive_line,
+            &mut self.transmit_line,
+            &mut self.error,
+            &mut self.info,
+        ] {
+            *style = f(*style);
+        }
+        self
+    }
+
+    /// The theme cut down to the colors the terminal can show.
+    ///
+    /// Without any colors, whatever stood out by its background is reversed instead, and so is
+    /// the selected row whatever its style, so it can still be found.
+    pub fn for_terminal(self) -> Self {
+        let support = ColorSupport::detect();
+        let mut theme = self.map(|style| {
+            let adapted = Style {
+                fg: style.fg.and_then(|color| support.adapt(color)),
+                bg: style.bg.and_then(|color| support.adapt(color)),
+                ..style
+            };
+            if style.bg.is_some() && adapted.bg.is_none() {
+                adapted.add_modifier(Modifier::REVERSED)
+            } else {
+                adapted
+            }
+        });
+        if support == ColorSupport::None {
+            theme.selected = theme.selected.add_modifier(Modifier::REVERSED);
+        }
+        theme
+    }
+
+    /// A bordered block with the theme's border and title styles.
+    pub fn block<'a>(&self, title: impl Into<Line<'a>>) -> Block<'a> {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(self.border)
+            .title(title)
+            .title_style(self.title)
+    }
+
+    /// A panel's block, with the focus border if it has focus.
+    pub fn panel_block<'a>(&self, title: impl Into<Line<'a>>, focused: bool) -> Block<'a> {
+        let block = self.block(title);
+        if focused {
+            block.border_style(self.focus)
+        } else {
+            block
+        }
+    }
+}
+
+/// Parses a style as written in the config file: a foreground color, optionally followed by
+/// `on` and a background color, with any of `bold`, `dim`, `italic`, `underlined` and
+/// `reversed` mixed in. For example `"bold yellow on blue"`.
+///
+/// Colors are names like `red` or `light-cyan`, `#rrggbb`, or an index into the 256-color
+/// palette.
+fn parse_style(text: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    let mut words = text.split_whitespace();
+    while let Some(word) = words.next() {
+        let modifier = match word.to_lowercase().as_str() {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            "on" => {
+                let color = words
+                    .next()
+                    .ok_or_else(|| format!("`{text}` is missing a color after `on`"))?;
+                style = style.bg(parse_color(color)?);
+                continue;
+            }
+            _ => {
+                style = style.fg(parse_color(word)?);
+                continue;
+            }
+        };
+        style = style.add_modifier(modifier);
+    }
+    Ok(style)
+}
+
+fn parse_color(text: &str) -> Result<Color, String> {
+    Color::from_str(text).map_err(|_| format!("`{text}` is not a color"))
+}
+
+/// How many colors the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorSupport {
+    /// `NO_COLOR` is set.
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// The 16 ANSI colors as xterm draws them by default, for finding the closest one.
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// The levels of each channel in the 6x6x6 cube of the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorSupport {
+    /// Goes by the environment, as there's no reliable way to ask the terminal itself.
+    fn detect() -> Self {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        // https://no-color.org: set and not empty.
+        if !var("NO_COLOR").is_empty() {
+            return Self::None;
+        }
+        let colorterm = var("COLORTERM");
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return Self::TrueColor;
+        }
+        if var("TERM").contains("256color") {
+            return Self::Ansi256;
+        }
+        Self::Ansi16
+    }
+
+    /// `color` as close as the terminal can get, or `None` to leave the terminal's own color.
+    fn adapt(self, color: Color) -> Option<Color> {
+        match (self, color) {
+            (Self::None, _) => None,
+            (Self::TrueColor, _) => Some(color),
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Some(Color::Indexed(nearest_indexed(r, g, b))),
+            (Self::Ansi16, Color::Rgb(r, g, b)) => Some(nearest_ansi(r, g, b)),
+            (Self::Ansi16, Color::Indexed(index)) => {
+                let (r, g, b) = indexed_rgb(index);
+                Some(nearest_ansi(r, g, b))
+            }
+            _ => Some(color),
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    channel(r1, r2) + channel(g1, g2) + channel(b1, b2)
+}
+
+fn nearest_ansi(r: u8, g: u8, b: u8) -> Color {
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|&&(_, rgb)| distance(rgb, (r, g, b)))
+        .map_or(Color::Reset, |&(color, _)| color)
+}
+
+/// The closest entry in the 256-color palette, from the color cube or the gray ramp.
+fn nearest_indexed(r: u8, g: u8, b: u8) -> u8 {
+    let level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&level| (CUBE_LEVELS[level] as i32 - channel as i32).abs())
+            .unwrap_or(0) as u8
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    // The ramp runs from 8 to 238 in steps of 10.
+    let gray = 232 + ((average.saturating_sub(3)) / 10).min(23) as u8;
+    if distance(indexed_rgb(gray), (r, g, b)) < distance(indexed_rgb(cube), (r, g, b)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// The color xterm shows for a palette index.
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..16 => ANSI_COLORS[index as usize].1,
+        16..232 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Arranging processes as a forest under their parents, like `ps --forest`.
+
+use std::collections::{HashMap, HashSet};
+
+use sysinfo::Pid;
+
+use crate::process::ProcessRow;
+
+/// One line of the flattened tree.
+#[derive(Debug)]
+pub struct TreeRow {
+    /// Index into the rows the tree was built from.
+    pub index: usize,
+    /// The guide drawn before the name, e.g. `"│ ├─ "`.
+    pub prefix: String,
+    pub has_children: bool,
+    /// For a collapsed node, the node's row with its usage summed over its whole subtree.
+    pub totals: Option<ProcessRow>,
+}
+
+/// Flattens `rows` into tree order. Siblings keep their relative order from `rows`, so sorting
+/// the input sorts each level of the tree. Children of `collapsed` PIDs are left out, and only
+/// processes that satisfy `keep` or have a descendant that does are included.
+pub fn flatten(
+    rows: &[ProcessRow],
+    collapsed: &HashSet<Pid>,
+    keep: impl Fn(&ProcessRow) -> bool,
+) -> Vec<TreeRow> {
+    let positions: HashMap<Pid, usize> = rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| (row.pid, index))
+        .collect();
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); rows.len()];
+    let mut roots = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        match row.parent.and_then(|parent| positions.get(&parent)) {
+            Some(&parent) if parent != index => children[parent].push(index),
+            _ => roots.push(index),
+        }
+    }
+
+    let mut builder = Builder {
+        rows,
+        children: &children,
+        collapsed,
+        kept: vec![false; rows.len()],
+        out: Vec::new(),
+    };
+    for &root in &roots {
+        builder.mark_kept(root, &keep);
+    }
+    let roots: Vec<_> = roots
+        .into_iter()
+        .filter(|&root| builder.kept[root])
+        .collect();
+    for (position, &root) in roots.iter().enumerate() {
+        builder.push(root, "", position + 1 == roots.len(), true);
+    }
+    builder.out
+}
+
+struct Builder<'a> {
+    rows: &'a [ProcessRow],
+    children: &'a [Vec<usize>],
+    collapsed: &'a HashSet<Pid>,
+    /// Whether each row survives the `keep` filter, directly or through a descendant.
+    kept: Vec<bool>,
+    out: Vec<TreeRow>,
+}
+
+impl Builder<'_> {
+    fn mark_kept(&mut self, index: usize, keep: &impl Fn(&ProcessRow) -> bool) -> bool {
+        let mut kept = keep(&self.rows[index]);
+        for &child in &self.children[index] {
+            kept |= self.mark_kept(child, keep);
+        }
+        self.kept[index] = kept;
+        kept
+    }
+
+    /// `indent` is the guide inherited from the ancestors; roots get no guide at all.
+    fn push(&mut self, index: usize, indent: &str, last: bool, root: bool) {
+        let row = &self.rows[index];
+        let children: Vec<_> = self.children[index]
+            .iter()
+            .copied()
+            .filter(|&child| self.kept[child])
+            .collect();
+        let collapsed = !children.is_empty() && self.collapsed.contains(&row.pid);
+
+        let branch = match (root, last) {
+            (true, _) => "",
+            (false, false) => "├─",
+            (false, true) => "└─",
+        };
+        let marker = if collapsed { "+" } else { " " };
+        let prefix = if root && !collapsed {
+            String::new()
+        } else {
+            format!("{indent}{branch}{marker}")
+        };
+
+        self.out.push(TreeRow {
+            index,
+            prefix,
+            has_children: !children.is_empty(),
+            totals: collapsed.then(|| self.totals(index)),
+        });
+        if collapsed {
+            return;
+        }
+
+        let indent = match (root, last) {
+            (true, _) => String::new(),
+            (false, false) => format!("{indent}│ "),
+            (false, true) => format!("{indent}  "),
+        };
+        for (position, &child) in children.iter().enumerate() {
+            self.push(child, &indent, position + 1 == children.len(), false);
+        }
+    }
+
+    fn totals(&self, index: usize) -> ProcessRow {
+        let mut totals = self.rows[index].clone();
+        let mut stack = self.children[index].clone();
+        while let Some(child) = stack.pop() {
+            let row = &self.rows[child];
+            totals.cpu += row.cpu;
+            totals.memory += row.memory;
+            totals.memory_percent += row.memory_percent;
+            totals.virtual_memory += row.virtual_memory;
+            totals.cpu_time += row.cpu_time;
+            totals.disk_read += row.disk_read;
+            totals.disk_write += row.disk_write;
+            stack.extend_from_slice(&self.children[child]);
+        }
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sysinfo::ProcessStatus;
+
+    use super::*;
+
+    fn row(pid: u32, parent: Option<u32>, memory: u64) -> ProcessRow {
+        ProcessRow {
+            pid: Pid::from_u32(pid),
+            parent: parent.map(Pid::from_u32),
+            name: format!("p{pid}"),
+            cpu: 1.0,
+            memory,
+            memory_percent: memory as f32 / 10.0,
+            virtual_memory: memory * 2,
+            cpu_time: 10,
+            start_time: 0,
+            disk_read: 0.0,
+            disk_write: 0.0,
+            command: String::new(),
+            exe: String::new(),
+            cwd: String::new(),
+            user: None,
+            status: ProcessStatus::Run,
+            threads: None,
+            priority: None,
+            nice: None,
+        }
+    }
+
+    /// 1 ─┬─ 2 ── 4
+    ///    └─ 3
+    fn forest() -> Vec<ProcessRow> {
+        vec![
+            row(1, None, 100),
+            row(2, Some(1), 20),
+            row(3, Some(1), 30),
+            row(4, Some(2), 40),
+        ]
+    }
+
+    fn pids(rows: &[ProcessRow], tree: &[TreeRow]) -> Vec<u32> {
+        tree.iter()
+            .map(|row| rows[row.index].pid.as_u32())
+            .collect()
+    }
+
+    #[test]
+    fn draws_guides_in_tree_order() {
+        let rows = forest();
+        let tree = flatten(&rows, &HashSet::new(), |_| true);
+        assert_eq!(pids(&rows, &tree), [1, 2, 4, 3]);
+        let prefixes: Vec<_> = tree.iter().map(|row| row.prefix.as_str()).collect();
+        assert_eq!(prefixes, ["", "├─ ", "│ └─ ", "└─ "]);
+        let has_children: Vec<_> = tree.iter().map(|row| row.has_children).collect();
+        assert_eq!(has_children, [true, true, false, false]);
+    }
+
+    #[test]
+    fn keeps_sibling_order() {
+        let mut rows = forest();
+        rows.swap(1, 2);
+        let tree = flatten(&rows, &HashSet::new(), |_| true);
+        assert_eq!(pids(&rows, &tree), [1, 3, 2, 4]);
+    }
+
+    #[test]
+    fn collapsed_node_sums_its_subtree() {
+        let rows = forest();
+        let collapsed = HashSet::from([Pid::from_u32(2)]);
+        let tree = flatten(&rows, &collapsed, |_| true);
+        assert_eq!(pids(&rows, &tree), [1, 2, 3]);
+        assert_eq!(tree[1].prefix, "├─+");
+        let totals = tree[1].totals.as_ref().unwrap();
+        assert_eq!(totals.memory, 60);
+        assert_eq!(totals.memory_percent, 6.0);
+        assert_eq!(totals.virtual_memory, 120);
+        assert_eq!(totals.cpu_time, 20);
+        assert!(tree[0].totals.is_none());
+    }
+
+    #[test]
+    fn filter_keeps_ancestors_of_matches() {
+        let rows = forest();
+        let tree = flatten(&rows, &HashSet::new(), |row| row.pid.as_u32() == 4);
+        assert_eq!(pids(&rows, &tree), [1, 2, 4]);
+        assert_eq!(tree[1].prefix, "└─ ");
+    }
+
+    #[test]
+    fn orphans_and_self_parents_are_roots() {
+        let rows = vec![row(7, Some(99), 1), row(8, Some(8), 1)];
+        let tree = flatten(&rows, &HashSet::new(), |_| true);
+        assert_eq!(pids(&rows, &tree), [7, 8]);
+        assert!(tree.iter().all(|row| row.prefix.is_empty()));
+    }
+}
@@ -0,0 +1,140 @@
+//! Terminal setup and teardown.
+//!
+//! vtop takes over the whole terminal while it runs: raw mode, the alternate screen and mouse
+//! capture. Everything here exists to make sure the user's shell gets the terminal back in a
+//! usable state no matter how we exit — normally, with an error, on a panic, or when killed.
+
+use std::io::{self, Stdout};
+use std::ops::{Deref, DerefMut};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use color_eyre::{Result, config::HookBuilder};
+use crossterm::{
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute, queue,
+    terminal::{
+        self, BeginSynchronizedUpdate, EndSynchronizedUpdate, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+use ratatui::{Frame, Terminal, backend::CrosstermBackend};
+
+pub type Tui = Terminal<CrosstermBackend<Stdout>>;
+
+/// Set while the terminal is in raw mode / the alternate screen, so that [`restore`] only undoes
+/// what [`TerminalGuard::new`] did and is safe to call from several places.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Installs the error, panic and signal hooks. Must be called once, before [`TerminalGuard::new`].
+///
+/// The panic hook restores the terminal before handing off to color_eyre's, otherwise the report
+/// would be printed to the alternate screen and lost.
+pub fn install_hooks() -> Result<()> {
+    let (panic_hook, eyre_hook) = HookBuilder::default().into_hooks();
+    eyre_hook.install()?;
+
+    let panic_hook = panic_hook.into_panic_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        panic_hook(info);
+    }));
+
+    #[cfg(unix)]
+    spawn_signal_handler()?;
+
+    Ok(())
+}
+
+/// Restores the terminal on SIGTERM and SIGHUP, then exits with the conventional `128 + signal`
+/// status. Without this the default action kills us with the terminal still in raw mode.
+#[cfg(unix)]
+fn spawn_signal_handler() -> Result<()> {
+    use signal_hook::{
+        consts::{SIGHUP, SIGTERM},
+        iterator::Signals,
+    };
+
+    let mut signals = Signals::new([SIGTERM, SIGHUP])?;
+    std::thread::spawn(move || {
+        if let Some(signal) = signals.forever().next() {
+            let _ = restore();
+            std::process::exit(128 + signal);
+        }
+    });
+    Ok(())
+}
+
+/// Owns the terminal for the lifetime of the app and restores it when dropped.
+pub struct TerminalGuard {
+    terminal: Tui,
+}
+
+impl TerminalGuard {
+    pub fn new() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        // There's no guard yet to restore the terminal when dropped, so do it here if the rest of
+        // the setup fails.
+        Self::enter().inspect_err(|_| {
+            let _ = restore();
+        })
+    }
+
+    fn enter() -> Result<Self> {
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        terminal.clear()?;
+        Ok(Self { terminal })
+    }
+}
+
+impl Deref for TerminalGuard {
+    type Target = Tui;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore();
+    }
+}
+
+/// Draws a frame as one synchronized update, so terminals that support it show the result all
+/// at once. After a resize ratatui clears the screen before redrawing; without this the blank
+/// screen can flash up in between.
+pub fn draw(terminal: &mut Tui, render: impl FnOnce(&mut Frame)) -> io::Result<()> {
+    queue!(terminal.backend_mut(), BeginSynchronizedUpdate)?;
+    let drawn = terminal.draw(render).map(drop);
+    // End the update even if drawing failed, or the terminal would stop showing anything.
+    let ended = execute!(terminal.backend_mut(), EndSynchronizedUpdate);
+    drawn.and(ended)
+}
+
+/// Leaves raw mode and the alternate screen and shows the cursor again. Does nothing if the
+/// terminal has already been restored.
+pub fn restore() -> io::Result<()> {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    // Try every step even if an earlier one fails; a half-restored terminal is still better than
+    // one left entirely in raw mode.
+    let raw = terminal::disable_raw_mode();
+    let screen = execute!(
+        io::stdout(),
+        DisableMouseCapture,
+        LeaveAlternateScreen,
+        cursor::Show
+    );
+    raw.and(screen)
+}